@@ -0,0 +1,60 @@
+//! A snapshot of a running session, saved so it can be picked up again after a crash.
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::data;
+use crate::engine::StateMachine;
+use crate::interval::Interval;
+
+/// Everything needed to pick a session up where it was left: the mode, counts and task, and the
+/// current interval. Taken with [`Timer::checkpoint`](crate::Timer::checkpoint) and turned back
+/// into a timer with [`Timer::restore`](crate::Timer::restore).
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Checkpoint {
+    pub(crate) state: StateMachine,
+    pub(crate) interval: Interval,
+    pub(crate) paused: bool,
+    pub(crate) saved_at: DateTime<Local>,
+}
+
+impl Checkpoint {
+    /// The wall-clock time the checkpoint was taken at.
+    pub fn saved_at(&self) -> DateTime<Local> {
+        self.saved_at
+    }
+}
+
+/// The checkpoint lives next to the history, e.g. ~/.local/share/pomodoro/session.json
+pub fn path() -> io::Result<PathBuf> {
+    data::file("session.json")
+}
+
+/// Replaces the saved checkpoint. A crash halfway through leaves the previous one intact.
+pub fn save(checkpoint: &Checkpoint) -> io::Result<()> {
+    data::write_atomically(&path()?, serde_json::to_string(checkpoint)?.as_bytes())
+}
+
+/// The saved checkpoint, if there is one.
+pub fn load() -> io::Result<Option<Checkpoint>> {
+    let contents = match fs::read_to_string(path()?) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    serde_json::from_str(&contents)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Forgets the saved checkpoint once the session is over.
+pub fn clear() -> io::Result<()> {
+    match fs::remove_file(path()?) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
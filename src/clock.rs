@@ -0,0 +1,151 @@
+//! Where the timer gets the time from.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+
+/// A source of time for the [`Timer`](crate::Timer) and its intervals.
+pub trait Clock {
+    /// Time passed on this clock since it was created. It never goes backwards, and doesn't
+    /// count time the system spent suspended.
+    fn elapsed(&self) -> Duration;
+
+    /// The current wall-clock time as this clock sees it. Unlike `elapsed` it keeps going while
+    /// the system is suspended, and it may jump when the system clock is set.
+    fn wall(&self) -> DateTime<Local>;
+
+    /// Time the system spent suspended since this clock was created.
+    fn suspended(&self) -> Duration;
+}
+
+/// The real clock, optionally running faster than real time for demos.
+#[derive(Debug)]
+pub struct SystemClock {
+    start: Instant,
+    wall_start: DateTime<Local>,
+    speed: u32,
+    // how far the boot-time clock was ahead of the monotonic one when this clock was created
+    #[cfg(target_os = "linux")]
+    suspended_start: Duration,
+}
+
+impl SystemClock {
+    /// A clock running at real time.
+    pub fn new() -> SystemClock {
+        SystemClock::with_speed(1)
+    }
+
+    /// A clock running `speed` times faster than real time.
+    pub fn with_speed(speed: u32) -> SystemClock {
+        SystemClock {
+            start: Instant::now(),
+            wall_start: Local::now(),
+            speed,
+            #[cfg(target_os = "linux")]
+            suspended_start: suspended_since_boot(),
+        }
+    }
+
+    // The boot-time clock keeps going while the system is suspended and the monotonic one
+    // doesn't, while neither of them moves when the system clock is set.
+    #[cfg(target_os = "linux")]
+    fn real_suspended(&self) -> Duration {
+        suspended_since_boot().saturating_sub(self.suspended_start)
+    }
+
+    // Without a boot-time clock, the wall clock running ahead of the monotonic one is the best
+    // there is. It takes setting the system clock forward for a suspend too.
+    #[cfg(not(target_os = "linux"))]
+    fn real_suspended(&self) -> Duration {
+        (Local::now() - self.wall_start)
+            .to_std()
+            .unwrap_or_default()
+            .saturating_sub(self.start.elapsed())
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn suspended_since_boot() -> Duration {
+    fn read(clock: libc::clockid_t) -> Duration {
+        let mut time = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        // SAFETY: `time` is a valid timespec to write to, and both clocks exist since Linux 2.6.39
+        unsafe { libc::clock_gettime(clock, &mut time) };
+        Duration::new(time.tv_sec as u64, time.tv_nsec as u32)
+    }
+    read(libc::CLOCK_BOOTTIME).saturating_sub(read(libc::CLOCK_MONOTONIC))
+}
+
+impl Default for SystemClock {
+    fn default() -> SystemClock {
+        SystemClock::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn elapsed(&self) -> Duration {
+        self.start.elapsed() * self.speed
+    }
+
+    fn wall(&self) -> DateTime<Local> {
+        self.wall_start + (Local::now() - self.wall_start) * self.speed as i32
+    }
+
+    fn suspended(&self) -> Duration {
+        self.real_suspended() * self.speed
+    }
+}
+
+/// A clock that only moves when told to, for tests and simulations. Clones share the same
+/// time, so one can be handed to a `Timer` while another one drives it.
+#[derive(Clone, Debug)]
+pub struct VirtualClock {
+    elapsed: Arc<Mutex<Duration>>,
+    // time spent "suspended", which only the wall clock sees
+    suspended: Arc<Mutex<Duration>>,
+    wall_start: DateTime<Local>,
+}
+
+impl VirtualClock {
+    /// A clock standing at the current wall-clock time.
+    pub fn new() -> VirtualClock {
+        VirtualClock {
+            elapsed: Arc::new(Mutex::new(Duration::from_secs(0))),
+            suspended: Arc::new(Mutex::new(Duration::from_secs(0))),
+            wall_start: Local::now(),
+        }
+    }
+
+    /// Moves the clock forward.
+    pub fn advance(&self, by: Duration) {
+        *self.elapsed.lock().unwrap() += by;
+    }
+
+    /// Moves only the wall clock forward, as if the system was suspended for `by`.
+    pub fn suspend(&self, by: Duration) {
+        *self.suspended.lock().unwrap() += by;
+    }
+}
+
+impl Default for VirtualClock {
+    fn default() -> VirtualClock {
+        VirtualClock::new()
+    }
+}
+
+impl Clock for VirtualClock {
+    fn elapsed(&self) -> Duration {
+        *self.elapsed.lock().unwrap()
+    }
+
+    fn wall(&self) -> DateTime<Local> {
+        self.wall_start + (self.elapsed() + self.suspended())
+    }
+
+    fn suspended(&self) -> Duration {
+        *self.suspended.lock().unwrap()
+    }
+}
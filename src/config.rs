@@ -0,0 +1,254 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use pomodoro::SuspendPolicy;
+use serde::de;
+use serde::{Deserialize, Deserializer};
+
+// Settings read from the config file. Every key is optional; missing ones keep the defaults
+// below, and command-line flags win over both.
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub pomodoro_duration: Duration,
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub break_duration: Duration,
+    pub max_pomodoros: u8,
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub long_break_duration: Duration,
+    pub long_break_every: u8,
+    pub auto_start_breaks: bool,
+    pub auto_start_pomodoros: bool,
+    // how long the countdown before auto-starting lasts
+    #[serde(deserialize_with = "deserialize_delay")]
+    pub auto_start_delay: Duration,
+    pub on_suspend: SuspendPolicy,
+    // how much the extend and shorten keys add or take off
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub adjust_by: Duration,
+    pub keys: Keys,
+    pub sounds: SoundFiles,
+    pub mute: bool,
+    pub notify: bool,
+    // one-line view instead of the full screen
+    pub compact: bool,
+    // whether finished intervals are written to the history
+    pub history: bool,
+    pub hooks: Hooks,
+    pub todo: Todo,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            pomodoro_duration: Duration::from_secs(25 * 60),
+            break_duration: Duration::from_secs(4 * 60),
+            max_pomodoros: 4,
+            long_break_duration: Duration::from_secs(15 * 60),
+            long_break_every: 4,
+            auto_start_breaks: false,
+            auto_start_pomodoros: false,
+            auto_start_delay: Duration::from_secs(10),
+            on_suspend: SuspendPolicy::Count,
+            adjust_by: Duration::from_secs(5 * 60),
+            keys: Keys::default(),
+            sounds: SoundFiles::default(),
+            mute: false,
+            notify: false,
+            compact: false,
+            history: true,
+            hooks: Hooks::default(),
+            todo: Todo::default(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Keys {
+    pub pause: char,
+    pub quit: char,
+    pub task: char,
+    pub skip: char,
+    pub extend: char,
+    pub shorten: char,
+    pub restart: char,
+    pub void: char,
+    pub internal_interruption: char,
+    pub external_interruption: char,
+    // moving through and finishing items of the todo.txt picker, besides the arrow keys
+    pub up: char,
+    pub down: char,
+    pub done: char,
+}
+
+impl Default for Keys {
+    fn default() -> Keys {
+        Keys {
+            pause: 'p',
+            quit: 'q',
+            task: 't',
+            skip: 's',
+            extend: '+',
+            shorten: '_',
+            restart: 'r',
+            void: 'v',
+            internal_interruption: '\'',
+            external_interruption: '-',
+            up: 'k',
+            down: 'j',
+            done: 'x',
+        }
+    }
+}
+
+// Unset sounds play the embedded gong.
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct SoundFiles {
+    pub pomodoro_end: Option<PathBuf>,
+    pub break_end: Option<PathBuf>,
+    pub done: Option<PathBuf>,
+}
+
+// Shell commands to run on each transition, see `hooks::run`.
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Hooks {
+    pub on_pomodoro_start: Option<String>,
+    pub on_pomodoro_end: Option<String>,
+    pub on_break_start: Option<String>,
+    pub on_break_end: Option<String>,
+    pub on_pause: Option<String>,
+    pub on_resume: Option<String>,
+    pub on_quit: Option<String>,
+    pub on_done: Option<String>,
+}
+
+// A todo.txt file to pick each pomodoro's task from.
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Todo {
+    pub file: Option<PathBuf>,
+    // whether completed pomodoros are counted in the item's pomo:N tag
+    pub pomo_tags: bool,
+}
+
+// Accepts humantime-style durations such as "50m", "1h15m" or "90s". Bare numbers are minutes,
+// as they've always been.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let duration = parse_delay(s)?;
+    if duration == Duration::from_secs(0) {
+        return Err("duration must be longer than zero".to_string());
+    }
+    Ok(duration)
+}
+
+// Like `parse_duration`, but zero is fine: nothing to wait for.
+pub fn parse_delay(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    if !s.is_empty() && s.chars().all(|c| c.is_ascii_digit()) {
+        let minutes: u64 = s.parse().map_err(|e| format!("{}", e))?;
+        let secs = minutes
+            .checked_mul(60)
+            .ok_or_else(|| "duration is too long".to_string())?;
+        Ok(Duration::from_secs(secs))
+    } else {
+        humantime::parse_duration(s).map_err(|e| e.to_string())
+    }
+}
+
+// Durations in the config file are either a number of minutes or a string for `parse_duration`.
+fn deserialize_duration<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_with(deserializer, parse_duration)
+}
+
+fn deserialize_delay<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_with(deserializer, parse_delay)
+}
+
+fn deserialize_with<'de, D>(
+    deserializer: D,
+    parse: fn(&str) -> Result<Duration, String>,
+) -> Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Minutes(u64),
+        Text(String),
+    }
+
+    match Raw::deserialize(deserializer)? {
+        Raw::Minutes(minutes) => parse(&minutes.to_string()),
+        Raw::Text(text) => parse(&text),
+    }
+    .map_err(de::Error::custom)
+}
+
+// ~/.config/pomodoro/config.toml on Linux
+pub fn default_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("pomodoro").join("config.toml"))
+}
+
+// Loads the file at `path`, or the default location when no path is given. Only an explicitly
+// requested file has to exist.
+pub fn load(path: Option<&Path>) -> io::Result<Config> {
+    let (path, required) = match path {
+        Some(path) => (path.to_path_buf(), true),
+        None => match default_path() {
+            Some(path) => (path, false),
+            None => return Ok(Config::default()),
+        },
+    };
+
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound && !required => return Ok(Config::default()),
+        Err(e) => {
+            return Err(io::Error::new(
+                e.kind(),
+                format!("{}: {}", path.display(), e),
+            ))
+        }
+    };
+
+    toml::from_str(&contents).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", path.display(), e),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("25"), Ok(Duration::from_secs(25 * 60)));
+        assert_eq!(parse_duration("1h15m"), Ok(Duration::from_secs(75 * 60)));
+        assert_eq!(parse_duration(" 90s "), Ok(Duration::from_secs(90)));
+        assert!(parse_duration("0").is_err());
+        assert_eq!(parse_delay("0"), Ok(Duration::from_secs(0)));
+        assert!(parse_duration("soon").is_err());
+    }
+
+    #[test]
+    fn rejects_minutes_that_overflow() {
+        assert!(parse_duration(&u64::MAX.to_string()).is_err());
+        assert!(parse_duration(&(u64::MAX / 60 + 1).to_string()).is_err());
+    }
+}
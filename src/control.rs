@@ -0,0 +1,232 @@
+use std::env;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::fs::DirBuilder;
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use pomodoro::interval::countdown;
+use pomodoro::Timer;
+use serde::{Deserialize, Serialize};
+
+// What can be asked of the daemon, one JSON object per line, e.g. {"command":"pause"} or
+// {"command":"set-task","task":"Write RFC"}.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+pub enum Request {
+    // begins a session, or the next interval when one is waiting to start
+    Start,
+    Pause,
+    Resume,
+    // pauses or resumes, whichever applies
+    Toggle,
+    Skip,
+    Stop,
+    Status,
+    SetTask {
+        #[serde(default)]
+        task: Option<String>,
+    },
+}
+
+// The daemon answers every request with one line: the timer's status after carrying it out, or
+// why it couldn't.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Response {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<Status>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Status {
+    // a `Mode` name, or "idle" when no session is running
+    pub mode: String,
+    pub count: u8,
+    pub max_pomodoros: u8,
+    pub remaining_secs: u64,
+    pub paused: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task: Option<String>,
+}
+
+impl Status {
+    pub fn of(timer: Option<&Timer>) -> Status {
+        match timer {
+            Some(timer) => Status {
+                mode: timer.mode().name().to_string(),
+                count: timer.state().count(),
+                max_pomodoros: timer.state().max_pomodoros(),
+                remaining_secs: timer.interval().remaining().as_secs(),
+                paused: timer.is_paused(),
+                task: timer.state().task().map(String::from),
+            },
+            None => Status {
+                mode: "idle".to_string(),
+                count: 0,
+                max_pomodoros: 0,
+                remaining_secs: 0,
+                paused: false,
+                task: None,
+            },
+        }
+    }
+
+    // Fills in `template`, e.g. "{icon} {remaining} #{count}", in a single pass so that a task
+    // like "Fix {count}" comes out as it is. Unknown placeholders are left alone.
+    pub fn render(&self, template: &str) -> String {
+        let mut rendered = String::new();
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            rendered.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let placeholder = rest
+                .find('}')
+                .and_then(|end| Some((end, self.placeholder(&rest[1..end])?)));
+            match placeholder {
+                Some((end, value)) => {
+                    rendered.push_str(&value);
+                    rest = &rest[end + 1..];
+                }
+                None => {
+                    rendered.push('{');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        rendered.push_str(rest);
+        rendered
+    }
+
+    fn placeholder(&self, name: &str) -> Option<String> {
+        let value = match name {
+            "icon" => match self.mode.as_str() {
+                _ if self.paused => "⏸",
+                "pomodoro" | "pomodoro_ended" => "🍅",
+                "break" | "break_ended" | "long_break" | "long_break_ended" => "☕",
+                "done" => "✔",
+                _ => "",
+            }
+            .to_string(),
+            "mode" => self.mode.clone(),
+            "remaining" => self.remaining(),
+            "remaining_secs" => self.remaining_secs.to_string(),
+            "count" => self.count.to_string(),
+            "max_pomodoros" => self.max_pomodoros.to_string(),
+            "paused" => if self.paused { "paused" } else { "" }.to_string(),
+            "task" => self.task.clone().unwrap_or_default(),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    fn remaining(&self) -> String {
+        countdown(Duration::from_secs(self.remaining_secs))
+    }
+}
+
+// The status line, like the one the foreground timer shows in its compact view.
+impl Display for Status {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let remaining = self.remaining();
+        let paused = if self.paused { " (paused)" } else { "" };
+        let task = match &self.task {
+            Some(task) => format!(" - {}", task),
+            None => String::new(),
+        };
+
+        match self.mode.as_str() {
+            "pomodoro" => write!(
+                f,
+                "Pomodoro {}: {}{}{}",
+                self.count, remaining, paused, task
+            ),
+            "break" => write!(f, "Break {}: {}{}", self.count, remaining, paused),
+            "long_break" => write!(f, "Long break {}: {}{}", self.count, remaining, paused),
+            "pomodoro_ended" => write!(f, "Pomodoro {} ended{}", self.count, task),
+            "break_ended" | "long_break_ended" => write!(f, "Break {} ended{}", self.count, task),
+            "done" => write!(f, "Done"),
+            _ => write!(f, "Idle"),
+        }
+    }
+}
+
+// $XDG_RUNTIME_DIR/pomodoro.sock, e.g. /run/user/1000/pomodoro.sock. Where there's no runtime
+// dir, like on macOS, a directory of the user's own in the temp dir stands in for it, e.g.
+// /tmp/pomodoro-1000/pomodoro.sock.
+pub fn socket_path() -> io::Result<PathBuf> {
+    if let Some(dir) = dirs::runtime_dir() {
+        return Ok(dir.join("pomodoro.sock"));
+    }
+
+    // SAFETY: getuid can't fail and has no preconditions
+    let uid = unsafe { libc::getuid() };
+    let dir = env::temp_dir().join(format!("pomodoro-{}", uid));
+    match DirBuilder::new().mode(0o700).create(&dir) {
+        Ok(()) => (),
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => (),
+        Err(e) => return Err(e),
+    }
+    // the temp dir is shared, so someone else may have made it first to listen in
+    let metadata = fs::symlink_metadata(&dir)?;
+    if !metadata.is_dir() || metadata.uid() != uid || metadata.mode() & 0o077 != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("{} isn't private to this user", dir.display()),
+        ));
+    }
+    Ok(dir.join("pomodoro.sock"))
+}
+
+// Sends one request to the daemon listening on `path` and waits for its answer.
+pub fn send(path: &Path, request: &Request) -> io::Result<Response> {
+    let mut stream = UnixStream::connect(path)?;
+    let mut line = serde_json::to_string(request)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())?;
+
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)?;
+    Ok(serde_json::from_str(&line)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(task: &str) -> Status {
+        Status {
+            mode: "pomodoro".to_string(),
+            count: 2,
+            max_pomodoros: 4,
+            remaining_secs: 61 * 60 + 5,
+            paused: false,
+            task: Some(task.to_string()),
+        }
+    }
+
+    #[test]
+    fn renders_templates() {
+        let status = status("Write RFC");
+        assert_eq!(
+            status.render("{icon} {remaining} #{count}/{max_pomodoros} {task}"),
+            "🍅 1:01:05 #2/4 Write RFC"
+        );
+        assert_eq!(status.render("{unknown} {{count}} {"), "{unknown} {2} {");
+    }
+
+    #[test]
+    fn leaves_placeholders_in_the_task_alone() {
+        assert_eq!(
+            status("Fix {count} in {mode}").render("{task}"),
+            "Fix {count} in {mode}"
+        );
+    }
+}
@@ -0,0 +1,195 @@
+use std::fs;
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Duration;
+
+use pomodoro::Event as TimerEvent;
+use pomodoro::{Mode, Schedule, Timer};
+
+use crate::config::Config;
+use crate::control::{socket_path, Request, Response, Status};
+use crate::sound::Sounds;
+
+// A request from one of the connections, with a way to answer it.
+struct Message {
+    request: Request,
+    reply: Sender<Response>,
+}
+
+// Runs sessions in the background until killed, driven by requests on the control socket. It
+// starts out idle.
+pub fn run(
+    config: &Config,
+    schedule: Schedule,
+    sounds: &Sounds,
+    mut task: Option<String>,
+    speed: u32,
+) -> io::Result<()> {
+    let path = socket_path()?;
+    if UnixStream::connect(&path).is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            format!("a daemon is already listening on {}", path.display()),
+        ));
+    }
+    // left behind by a daemon that didn't get to clean up
+    let _ = fs::remove_file(&path);
+    let listener = UnixListener::bind(&path)
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+
+    let (tx, rx) = channel();
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let tx = tx.clone();
+            thread::spawn(move || serve(stream, tx));
+        }
+    });
+
+    let mut stdout = io::stdout();
+    let tick_rate = (Duration::from_millis(500) / speed).max(Duration::from_millis(10));
+    let mut session: Option<Timer> = None;
+    loop {
+        if let Some(timer) = session.as_mut() {
+            for event in timer.tick() {
+                crate::handle_event(&mut stdout, &event, timer, config, sounds);
+            }
+        }
+
+        let Message { request, reply } = match rx.recv_timeout(tick_rate) {
+            Ok(message) => message,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err(io::Error::other("stopped accepting connections"))
+            }
+        };
+
+        if let Request::SetTask { task: new_task } = &request {
+            task = new_task.clone();
+        }
+        let stop = matches!(request, Request::Stop);
+        let response = match execute(request, &mut session, &schedule, &task, speed) {
+            Ok(events) => {
+                if let Some(timer) = session.as_ref() {
+                    for event in &events {
+                        crate::handle_event(&mut stdout, event, timer, config, sounds);
+                    }
+                }
+                if stop {
+                    session = None;
+                }
+                // let a start or skip take effect before answering
+                if let Some(timer) = session.as_mut() {
+                    for event in timer.tick() {
+                        crate::handle_event(&mut stdout, &event, timer, config, sounds);
+                    }
+                }
+                Response {
+                    ok: true,
+                    error: None,
+                    status: Some(Status::of(session.as_ref())),
+                }
+            }
+            Err(e) => Response {
+                ok: false,
+                error: Some(e),
+                status: Some(Status::of(session.as_ref())),
+            },
+        };
+        // the client may have hung up already
+        let _ = reply.send(response);
+    }
+}
+
+// Carries out `request`, returning the events it caused for the caller to handle.
+fn execute(
+    request: Request,
+    session: &mut Option<Timer>,
+    schedule: &Schedule,
+    task: &Option<String>,
+    speed: u32,
+) -> Result<Vec<TimerEvent>, String> {
+    match request {
+        Request::Status => return Ok(Vec::new()),
+        Request::Start => {
+            match session {
+                Some(timer) if timer.mode() != Mode::End => timer.acknowledge(),
+                _ => {
+                    // the task list's active task may change between sessions
+                    *session = Some(crate::new_timer(schedule.clone(), task.clone(), speed));
+                }
+            }
+            return Ok(Vec::new());
+        }
+        Request::SetTask { task } => {
+            if let Some(timer) = session {
+                timer.set_task(task);
+            }
+            return Ok(Vec::new());
+        }
+        _ => (),
+    }
+
+    let timer = match session {
+        Some(timer) if timer.mode() != Mode::End => timer,
+        _ => return Err("no session running".to_string()),
+    };
+    match request {
+        Request::Pause | Request::Resume | Request::Toggle if !timer.mode().is_running() => {
+            Err("no interval running".to_string())
+        }
+        Request::Toggle => Ok(timer.toggle_pause().into_iter().collect()),
+        Request::Pause if !timer.is_paused() => Ok(timer.toggle_pause().into_iter().collect()),
+        Request::Resume if timer.is_paused() => Ok(timer.toggle_pause().into_iter().collect()),
+        // starts the next interval when the session is waiting for it
+        Request::Skip if timer.mode().is_waiting() => {
+            timer.acknowledge();
+            Ok(Vec::new())
+        }
+        Request::Skip => Ok(timer.skip()),
+        Request::Stop => Ok(vec![timer.quit()]),
+        _ => Ok(Vec::new()),
+    }
+}
+
+// Answers one connection's requests, one line each, until it hangs up.
+fn serve(stream: UnixStream, tx: Sender<Message>) {
+    let mut writer = match stream.try_clone() {
+        Ok(writer) => writer,
+        Err(_) => return,
+    };
+    for line in BufReader::new(stream).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => return,
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str(&line) {
+            Ok(request) => {
+                let (reply, response) = channel();
+                if tx.send(Message { request, reply }).is_err() {
+                    return;
+                }
+                match response.recv() {
+                    Ok(response) => response,
+                    Err(_) => return,
+                }
+            }
+            Err(e) => Response {
+                ok: false,
+                error: Some(format!("bad request: {}", e)),
+                status: None,
+            },
+        };
+        let mut line = serde_json::to_string(&response).unwrap();
+        line.push('\n');
+        if writer.write_all(line.as_bytes()).is_err() {
+            return;
+        }
+    }
+}
@@ -0,0 +1,47 @@
+//! Where the timer keeps its files, and how it writes them.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A file in the timer's directory under the XDG data dir, e.g.
+/// ~/.local/share/pomodoro/history.jsonl
+pub fn file(name: &str) -> io::Result<PathBuf> {
+    match dirs::data_dir() {
+        Some(dir) => Ok(dir.join("pomodoro").join(name)),
+        None => Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "no data directory for this platform",
+        )),
+    }
+}
+
+/// Replaces the file at `path` with `contents`, creating its directory if needed. They're
+/// written next to it first and moved into place, so a crash halfway through leaves the old file
+/// intact. A link is replaced by what it points to, and the file keeps its permissions.
+pub fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let path = match fs::canonicalize(path) {
+        Ok(path) => path,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => path.to_path_buf(),
+        Err(e) => return Err(e),
+    };
+    let name = match path.file_name() {
+        Some(name) => name.to_string_lossy(),
+        None => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} isn't a file", path.display()),
+            ))
+        }
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let temp = path.with_file_name(format!("{}.tmp", name));
+    fs::write(&temp, contents)?;
+    if let Ok(metadata) = fs::metadata(&path) {
+        fs::set_permissions(&temp, metadata.permissions())?;
+    }
+    fs::rename(temp, path)
+}
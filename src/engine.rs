@@ -0,0 +1,194 @@
+//! The state machine stepping through pomodoros and breaks.
+
+use serde::{Deserialize, Serialize};
+
+use crate::history;
+use crate::tasks::Task;
+
+/// Where the session is at. The `Entering*` modes are only passed through to set up the next
+/// interval; the `*Ended` modes wait for the user to acknowledge before moving on.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Mode {
+    /// About to start a pomodoro.
+    EnteringPomodoro,
+    /// A pomodoro is running.
+    Pomodoro,
+    /// A pomodoro ran out and a break is next.
+    PomodoroEnded,
+    /// About to start a break.
+    EnteringBreak,
+    /// A break is running.
+    Break,
+    /// A break ran out and a pomodoro is next.
+    BreakEnded,
+    /// About to start a long break.
+    EnteringLongBreak,
+    /// A long break is running.
+    LongBreak,
+    /// A long break ran out and a pomodoro is next.
+    LongBreakEnded,
+    /// The last pomodoro ran out.
+    End,
+}
+
+impl Mode {
+    /// A short snake_case name for the mode, e.g. `"long_break"`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mode::EnteringPomodoro | Mode::Pomodoro => "pomodoro",
+            Mode::PomodoroEnded => "pomodoro_ended",
+            Mode::EnteringBreak | Mode::Break => "break",
+            Mode::BreakEnded => "break_ended",
+            Mode::EnteringLongBreak | Mode::LongBreak => "long_break",
+            Mode::LongBreakEnded => "long_break_ended",
+            Mode::End => "done",
+        }
+    }
+
+    /// Whether an interval is counting down.
+    pub fn is_running(&self) -> bool {
+        self.history_kind().is_some()
+    }
+
+    /// Whether the session is waiting for an acknowledgement to go on.
+    pub fn is_waiting(&self) -> bool {
+        *self == Mode::PomodoroEnded || *self == Mode::BreakEnded || *self == Mode::LongBreakEnded
+    }
+
+    /// What a running interval is recorded as in the history. Only the running modes produce an
+    /// interval worth recording.
+    pub fn history_kind(&self) -> Option<history::Kind> {
+        match self {
+            Mode::Pomodoro => Some(history::Kind::Pomodoro),
+            Mode::Break => Some(history::Kind::Break),
+            Mode::LongBreak => Some(history::Kind::LongBreak),
+            _ => None,
+        }
+    }
+}
+
+/// Counts pomodoros and breaks and decides which mode comes next.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StateMachine {
+    pomodoro_count: u8,
+    break_count: u8,
+    max_pomodoros: u8,
+    // a long break replaces the regular one after every `long_break_every` pomodoros; 0 means
+    // never
+    long_break_every: u8,
+    // what the current (or next) pomodoro is spent on
+    task: Option<String>,
+    // the task list entry the task was taken from, if it was
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    task_id: Option<u32>,
+    mode: Mode,
+}
+
+impl StateMachine {
+    /// A session of `max_pomodoros`, about to enter the first one. A long break follows every
+    /// `long_break_every` pomodoros, 0 disables long breaks.
+    pub fn new(max_pomodoros: u8, long_break_every: u8, task: Option<String>) -> StateMachine {
+        StateMachine {
+            pomodoro_count: 1,
+            break_count: 1,
+            max_pomodoros,
+            long_break_every,
+            task,
+            task_id: None,
+            mode: Mode::EnteringPomodoro,
+        }
+    }
+
+    /// The current mode.
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// The number of the current pomodoro, starting at 1. It moves on to the next one as soon
+    /// as the break after a pomodoro starts.
+    pub fn pomodoro_count(&self) -> u8 {
+        self.pomodoro_count
+    }
+
+    /// The number of the current break, starting at 1.
+    pub fn break_count(&self) -> u8 {
+        self.break_count
+    }
+
+    /// How many pomodoros the session has.
+    pub fn max_pomodoros(&self) -> u8 {
+        self.max_pomodoros
+    }
+
+    /// The number shown next to the current pomodoro or break.
+    pub fn count(&self) -> u8 {
+        match self.mode {
+            Mode::EnteringBreak
+            | Mode::Break
+            | Mode::BreakEnded
+            | Mode::EnteringLongBreak
+            | Mode::LongBreak
+            | Mode::LongBreakEnded => self.break_count,
+            _ => self.pomodoro_count,
+        }
+    }
+
+    /// What the current (or next) pomodoro is spent on.
+    pub fn task(&self) -> Option<&str> {
+        self.task.as_deref()
+    }
+
+    /// The id of the task list entry the task was taken from, if it was.
+    pub fn task_id(&self) -> Option<u32> {
+        self.task_id
+    }
+
+    /// Changes what the current (or next) pomodoro is spent on.
+    pub fn set_task(&mut self, task: Option<String>) {
+        self.task = task;
+        self.task_id = None;
+    }
+
+    /// Spends the current (or next) pomodoro on an entry of the task list.
+    pub fn set_listed_task(&mut self, task: &Task) {
+        self.task = Some(task.name.clone());
+        self.task_id = Some(task.id);
+    }
+
+    /// Whether the break after the current pomodoro is a long one.
+    pub fn is_long_break_due(&self) -> bool {
+        self.long_break_every != 0 && self.pomodoro_count.is_multiple_of(self.long_break_every)
+    }
+
+    /// Moves on to the next mode.
+    pub fn next_state(&mut self) {
+        match self.mode {
+            Mode::EnteringPomodoro => self.mode = Mode::Pomodoro,
+            Mode::Pomodoro => {
+                if self.pomodoro_count == self.max_pomodoros {
+                    self.mode = Mode::End;
+                } else {
+                    self.mode = Mode::PomodoroEnded;
+                }
+            }
+            Mode::PomodoroEnded => {
+                if self.is_long_break_due() {
+                    self.mode = Mode::EnteringLongBreak;
+                } else {
+                    self.mode = Mode::EnteringBreak;
+                }
+                self.pomodoro_count += 1;
+            }
+            Mode::EnteringBreak => self.mode = Mode::Break,
+            Mode::Break => self.mode = Mode::BreakEnded,
+            Mode::EnteringLongBreak => self.mode = Mode::LongBreak,
+            Mode::LongBreak => self.mode = Mode::LongBreakEnded,
+            Mode::BreakEnded | Mode::LongBreakEnded => {
+                self.break_count += 1;
+                self.mode = Mode::EnteringPomodoro;
+            }
+            Mode::End => (),
+        }
+    }
+}
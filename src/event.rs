@@ -0,0 +1,36 @@
+//! What happened while the timer ran.
+
+use std::time::Duration;
+
+use crate::history::Entry;
+
+/// Returned by [`Timer`](crate::Timer) whenever the session moves along, in the order things
+/// happened. Events that finish an interval carry its history record.
+#[derive(Debug)]
+pub enum Event {
+    /// A pomodoro started running.
+    PomodoroStarted,
+    /// A pomodoro ran out or was skipped.
+    PomodoroEnded(Entry),
+    /// A break started running.
+    BreakStarted {
+        /// Whether it's a long break.
+        long: bool,
+    },
+    /// A break ran out or was skipped.
+    BreakEnded(Entry),
+    /// The running interval started over, with the record of the run that was cut short. Its
+    /// outcome tells why.
+    Restarted(Entry),
+    /// The system was suspended for this long. Followed by whatever the
+    /// [`SuspendPolicy`](crate::SuspendPolicy) calls for.
+    Suspended(Duration),
+    /// The running interval was paused.
+    Paused,
+    /// The running interval was resumed.
+    Resumed,
+    /// The session was cut short, with the interval that was running at the time.
+    Quit(Option<Entry>),
+    /// The last pomodoro ran out. Follows its `PomodoroEnded`.
+    Done,
+}
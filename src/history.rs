@@ -0,0 +1,149 @@
+//! The append-only log of finished intervals.
+
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::data;
+use crate::schedule::SuspendPolicy;
+
+/// What kind of interval an entry records.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    /// A pomodoro.
+    Pomodoro,
+    /// A regular break.
+    Break,
+    /// A long break.
+    LongBreak,
+}
+
+/// How an interval finished.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    /// It ran out.
+    Completed,
+    /// The timer was quit while it ran.
+    Quit,
+    /// The user moved on before it ran out.
+    Skipped,
+    /// The user started it over. The new run gets an entry of its own.
+    Restarted,
+    /// It was thrown away and started over, by the user or because the system was suspended
+    /// while it ran (see [`SuspendPolicy::Void`]).
+    Voided,
+}
+
+/// Where an interruption came from, as the Pomodoro Technique tells them apart.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum InterruptionKind {
+    /// The user's own urge to do something else, marked `'`.
+    Internal,
+    /// Someone or something else, marked `-`.
+    External,
+}
+
+/// An interruption logged during a pomodoro, which kept running.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Interruption {
+    /// Where it came from.
+    pub kind: InterruptionKind,
+    /// How far into the pomodoro it happened.
+    pub after_secs: u64,
+    /// What it was about.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// One line of the history file. Durations are stored in whole seconds to keep the file easy to
+/// read and process with other tools.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Entry {
+    /// What kind of interval it was.
+    pub kind: Kind,
+    /// What the pomodoro was spent on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task: Option<String>,
+    /// The wall-clock time it started at.
+    pub started_at: DateTime<Local>,
+    /// How long it was planned to take.
+    pub planned_secs: u64,
+    /// How much the user extended (positive) or shortened (negative) it by while it ran.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub adjusted_secs: i64,
+    /// How much of it ran, not counting pauses.
+    pub elapsed_secs: u64,
+    /// How long it was paused.
+    pub paused_secs: u64,
+    /// How long the system was suspended while it ran.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub suspended_secs: u64,
+    /// What was done about the suspended time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_suspend: Option<SuspendPolicy>,
+    /// Why it was voided, if the user said.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// The interruptions logged while it ran.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub interruptions: Vec<Interruption>,
+    /// How it finished.
+    pub outcome: Outcome,
+}
+
+fn is_zero<T: Default + PartialEq>(n: &T) -> bool {
+    *n == T::default()
+}
+
+/// The history lives under the XDG data dir, e.g. ~/.local/share/pomodoro/history.jsonl
+pub fn path() -> io::Result<PathBuf> {
+    data::file("history.jsonl")
+}
+
+/// Adds an entry to the end of the file: one JSON object per line, oldest first.
+pub fn append(entry: &Entry) -> io::Result<()> {
+    let path = path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+    file.write_all(line.as_bytes())
+}
+
+/// Reads back every entry, oldest first. A missing file just means nothing has been recorded
+/// yet. Lines that can't be read as an entry, like one cut short when the timer was killed while
+/// writing it, are left out and passed to `skipped` with their line number.
+pub fn read(mut skipped: impl FnMut(io::Error)) -> io::Result<Vec<Entry>> {
+    let file = match File::open(path()?) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut entries = Vec::new();
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => skipped(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: {}", i + 1, e),
+            )),
+        }
+    }
+    Ok(entries)
+}
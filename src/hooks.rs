@@ -0,0 +1,38 @@
+use std::io;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+// What a hook gets to know about the timer, passed on as environment variables.
+pub struct Context<'a> {
+    pub mode: &'a str,
+    pub count: u8,
+    pub remaining: Duration,
+    pub task: Option<&'a str>,
+}
+
+// Runs `shell_command` through `sh -c` without waiting for it, so a slow hook never holds up the
+// timer. Its output is discarded since it would only garble the raw-mode terminal.
+pub fn run(shell_command: &str, context: &Context) -> io::Result<()> {
+    let mut command = Command::new("sh");
+    command
+        .arg("-c")
+        .arg(shell_command)
+        .env("POMODORO_MODE", context.mode)
+        .env("POMODORO_COUNT", context.count.to_string())
+        .env(
+            "POMODORO_REMAINING",
+            context.remaining.as_secs().to_string(),
+        )
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    if let Some(task) = context.task {
+        command.env("POMODORO_TASK", task);
+    }
+    let mut child = command.spawn()?;
+
+    // reap the child once it's done so it doesn't linger as a zombie
+    thread::spawn(move || child.wait());
+    Ok(())
+}
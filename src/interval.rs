@@ -0,0 +1,195 @@
+//! A single pomodoro or break counting down.
+
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::ops::SubAssign;
+use std::time::Duration;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::clock::Clock;
+use crate::history;
+use crate::schedule::SuspendPolicy;
+
+/// Time spent in and planned for one pomodoro or break. Subtracting a `Duration` counts it
+/// down. It displays as the remaining `mm:ss`, or `h:mm:ss` from an hour on.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Interval {
+    started_at: DateTime<Local>,
+    elapsed: Duration,
+    paused: Duration,
+    duration: Duration,
+    // the duration it started with, before extending or shortening it
+    planned: Duration,
+    suspended: Duration,
+    on_suspend: Option<SuspendPolicy>,
+    interruptions: Vec<history::Interruption>,
+}
+
+impl Interval {
+    /// An interval of `duration` starting now, as far as `clock` is concerned.
+    pub fn new(duration: Duration, clock: &impl Clock) -> Interval {
+        Interval {
+            started_at: clock.wall(),
+            elapsed: Duration::from_secs(0),
+            paused: Duration::from_secs(0),
+            duration,
+            planned: duration,
+            suspended: Duration::from_secs(0),
+            on_suspend: None,
+            interruptions: Vec::new(),
+        }
+    }
+
+    /// Starts the interval over from now, keeping its duration.
+    pub fn restart(&mut self, clock: &impl Clock) {
+        self.started_at = clock.wall();
+        self.elapsed = Duration::from_secs(0);
+        self.paused = Duration::from_secs(0);
+        self.suspended = Duration::from_secs(0);
+        self.on_suspend = None;
+        self.interruptions.clear();
+    }
+
+    /// Makes the interval longer.
+    pub fn extend(&mut self, by: Duration) {
+        self.duration += by;
+    }
+
+    /// Makes the interval shorter. It ends right away if it gets shorter than what has already
+    /// run.
+    pub fn shorten(&mut self, by: Duration) {
+        self.duration = self.duration.checked_sub(by).unwrap_or_default();
+    }
+
+    /// The wall-clock time the interval started at.
+    pub fn started_at(&self) -> DateTime<Local> {
+        self.started_at
+    }
+
+    /// How long the interval is planned to take, including any extending or shortening.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// How much of the interval has run, not counting pauses.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// How long the interval has been paused.
+    pub fn paused(&self) -> Duration {
+        self.paused
+    }
+
+    /// Counts time spent paused, which doesn't count down the interval.
+    pub fn add_paused(&mut self, rhs: Duration) {
+        self.paused += rhs;
+    }
+
+    /// Accounts for time the system spent suspended according to `policy`: counted, or counted
+    /// as paused. Voided time isn't counted at all.
+    pub fn add_suspended(&mut self, gap: Duration, policy: SuspendPolicy) {
+        match policy {
+            SuspendPolicy::Count => self.elapsed += gap,
+            SuspendPolicy::Pause => self.paused += gap,
+            SuspendPolicy::Void => (),
+        }
+        self.suspended += gap;
+        self.on_suspend = Some(policy);
+    }
+
+    /// The interruptions logged so far.
+    pub fn interruptions(&self) -> &[history::Interruption] {
+        &self.interruptions
+    }
+
+    /// Logs an interruption at the current point of the interval.
+    pub fn interrupt(&mut self, kind: history::InterruptionKind) {
+        self.interruptions.push(history::Interruption {
+            kind,
+            after_secs: self.elapsed.as_secs(),
+            note: None,
+        });
+    }
+
+    /// Says what the latest interruption was about. Returns whether there is one.
+    pub fn note_interruption(&mut self, note: String) -> bool {
+        match self.interruptions.last_mut() {
+            Some(interruption) => {
+                interruption.note = Some(note);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether the interval ran out.
+    pub fn has_ended(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    /// How much of the interval is left.
+    pub fn remaining(&self) -> Duration {
+        if self.elapsed >= self.duration {
+            Duration::from_secs(0)
+        } else {
+            self.duration - self.elapsed
+        }
+    }
+
+    /// The history record for this interval.
+    pub fn to_history(
+        &self,
+        kind: history::Kind,
+        task: Option<&str>,
+        outcome: history::Outcome,
+    ) -> history::Entry {
+        history::Entry {
+            kind,
+            task: task.map(String::from),
+            started_at: self.started_at,
+            planned_secs: self.planned.as_secs(),
+            adjusted_secs: self.duration.as_secs() as i64 - self.planned.as_secs() as i64,
+            elapsed_secs: self.elapsed.as_secs(),
+            paused_secs: self.paused.as_secs(),
+            suspended_secs: self.suspended.as_secs(),
+            on_suspend: self.on_suspend,
+            reason: None,
+            interruptions: self.interruptions.clone(),
+            outcome,
+        }
+    }
+}
+
+impl SubAssign<Duration> for Interval {
+    #[allow(clippy::suspicious_op_assign_impl)]
+    fn sub_assign(&mut self, rhs: Duration) {
+        // count up on `elapsed` because Duration can't be negative
+        // per https://rust-lang-nursery.github.io/rust-cookbook/datetime/duration.html#measure-the-elapsed-time-between-two-code-sections
+        self.elapsed += rhs;
+    }
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&countdown(self.remaining()))
+    }
+}
+
+/// Formats time left the way an [`Interval`] displays it: `mm:ss`, or `h:mm:ss` from an hour
+/// on.
+pub fn countdown(remaining: Duration) -> String {
+    let secs = remaining.as_secs();
+    if secs >= 60 * 60 {
+        format!(
+            "{}:{:02}:{:02}",
+            secs / (60 * 60),
+            secs / 60 % 60,
+            secs % 60
+        )
+    } else {
+        format!("{:02}:{:02}", secs / 60, secs % 60)
+    }
+}
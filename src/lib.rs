@@ -0,0 +1,42 @@
+//! The timer engine behind the `pomodoro` binary.
+//!
+//! A [`Timer`] runs a session of pomodoros and breaks laid out by a [`Schedule`]. It doesn't
+//! talk to the terminal itself: the caller calls [`Timer::tick`] regularly and reacts to the
+//! [`Event`]s that come back, e.g. to play a sound or write the [`history`].
+//!
+//! Time comes from a [`Clock`]. A [`VirtualClock`] only moves when told to, which makes a whole
+//! session run instantly:
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! use pomodoro::{Event, Schedule, Timer, VirtualClock};
+//!
+//! let clock = VirtualClock::new();
+//! let mut timer = Timer::with_clock(Schedule::default(), None, clock.clone());
+//! assert!(matches!(timer.tick()[..], [Event::PomodoroStarted]));
+//!
+//! clock.advance(Duration::from_secs(25 * 60));
+//! assert!(matches!(timer.tick()[..], [Event::PomodoroEnded(_)]));
+//! ```
+#![warn(missing_docs)]
+
+pub mod checkpoint;
+pub mod clock;
+pub mod data;
+pub mod engine;
+pub mod event;
+pub mod history;
+pub mod interval;
+pub mod schedule;
+pub mod stats;
+pub mod tasks;
+pub mod timer;
+
+pub use checkpoint::Checkpoint;
+pub use clock::{Clock, SystemClock, VirtualClock};
+pub use engine::{Mode, StateMachine};
+pub use event::Event;
+pub use interval::Interval;
+pub use schedule::{Schedule, SuspendPolicy};
+pub use timer::Timer;
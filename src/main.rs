@@ -1,17 +1,39 @@
-use std::fmt;
-use std::fmt::{Display, Formatter};
+use std::collections::HashMap;
 use std::io;
-use std::io::{Cursor, Write};
-use std::ops::SubAssign;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{channel, RecvTimeoutError};
 use std::thread;
 use std::time::{Duration, Instant};
 
-use rodio::Source;
+use chrono::NaiveDate;
+use pomodoro::checkpoint;
+use pomodoro::history;
+use pomodoro::history::InterruptionKind;
+use pomodoro::stats;
+use pomodoro::tasks;
+use pomodoro::Event as TimerEvent;
+use pomodoro::{Clock, Mode, Schedule, SuspendPolicy, SystemClock, Timer};
 use structopt::StructOpt;
+use termion::cursor::HideCursor;
 use termion::event::Key;
 use termion::input::TermRead;
 use termion::raw::IntoRawMode;
+use termion::screen::AlternateScreen;
+
+mod config;
+mod control;
+mod daemon;
+mod hooks;
+mod notify;
+mod screen;
+mod sound;
+mod todo;
+
+use config::Config;
+use control::{Request, Status};
+use sound::{Sound, Sounds};
 
 // `mspc`'s tx and rx need to send and receive something of the same type. We use `Event`
 // here to wrap our mixed types in a container to appease the compiler. I haven't fully
@@ -20,130 +42,1048 @@ enum Event {
     Key(Key),
 }
 
-#[derive(PartialEq)]
-enum Mode {
-    EnteringPomodoro,
-    Pomodoro,
-    PomodoroEnded,
-    EnteringBreak,
-    Break,
-    BreakEnded,
-    End,
+// A line being typed in, and what it's for.
+enum Input {
+    Task(String),
+    Note(String),
+    Reason(String),
+}
+
+impl Input {
+    fn prompt(&self) -> &'static str {
+        match self {
+            Input::Task(_) => "Task for the next pomodoro",
+            Input::Note(_) => "What interrupted you? (optional)",
+            Input::Reason(_) => "Why void this pomodoro? (optional, Esc to keep it)",
+        }
+    }
+
+    fn text(&self) -> &str {
+        match self {
+            Input::Task(text) | Input::Note(text) | Input::Reason(text) => text,
+        }
+    }
+
+    fn text_mut(&mut self) -> &mut String {
+        match self {
+            Input::Task(text) | Input::Note(text) | Input::Reason(text) => text,
+        }
+    }
+}
+
+// Timer settings are optional here so that anything left out falls back to the config file.
+#[derive(StructOpt)]
+#[structopt(name = "pomodoro")]
+struct Opt {
+    /// Read settings from this file instead of ~/.config/pomodoro/config.toml
+    #[structopt(long, parse(from_os_str))]
+    config: Option<PathBuf>,
+
+    /// Pomodoro duration, e.g. 50m, 1h15m or 90s. Bare numbers are minutes [default: 25m]
+    #[structopt(short, long, parse(try_from_str = config::parse_duration))]
+    pomodoro_duration: Option<Duration>,
+
+    /// Break duration, e.g. 50m, 1h15m or 90s. Bare numbers are minutes [default: 4m]
+    #[structopt(short, long, parse(try_from_str = config::parse_duration))]
+    break_duration: Option<Duration>,
+
+    /// Number of pomodoros in a session [default: 4]
+    #[structopt(short, long)]
+    max_pomodoros: Option<u8>,
+
+    /// Long break duration, e.g. 50m, 1h15m or 90s. Bare numbers are minutes [default: 15m]
+    #[structopt(short, long, parse(try_from_str = config::parse_duration))]
+    long_break_duration: Option<Duration>,
+
+    /// Take a long break after this many pomodoros, 0 disables long breaks [default: 4]
+    #[structopt(short = "e", long)]
+    long_break_every: Option<u8>,
+
+    /// Start breaks without waiting for a key press
+    #[structopt(long)]
+    auto_start_breaks: bool,
+
+    /// Wait for a key press before breaks, even if the config auto-starts them
+    #[structopt(long, conflicts_with = "auto_start_breaks")]
+    no_auto_start_breaks: bool,
+
+    /// Start pomodoros without waiting for a key press
+    #[structopt(long)]
+    auto_start_pomodoros: bool,
+
+    /// Wait for a key press before pomodoros, even if the config auto-starts them
+    #[structopt(long, conflicts_with = "auto_start_pomodoros")]
+    no_auto_start_pomodoros: bool,
+
+    /// How long to count down before auto-starting, e.g. 10s. Bare numbers are minutes
+    /// [default: 10s]
+    #[structopt(long, parse(try_from_str = config::parse_delay))]
+    auto_start_delay: Option<Duration>,
+
+    /// What to do with time the system spent suspended during a pomodoro: count it, pause the
+    /// pomodoro, or void it and start over [default: count]
+    #[structopt(long, possible_values = &["count", "pause", "void"])]
+    on_suspend: Option<SuspendPolicy>,
+
+    /// How much the extend and shorten keys add or take off, e.g. 5m or 90s. Bare numbers are
+    /// minutes [default: 5m]
+    #[structopt(long, parse(try_from_str = config::parse_duration))]
+    adjust_by: Option<Duration>,
+
+    /// Sound file to play whenever an interval ends, instead of the gong
+    #[structopt(long, parse(from_os_str))]
+    sound: Option<PathBuf>,
+
+    /// Sound file to play when a pomodoro ends
+    #[structopt(long, parse(from_os_str))]
+    pomodoro_end_sound: Option<PathBuf>,
+
+    /// Sound file to play when a break ends
+    #[structopt(long, parse(from_os_str))]
+    break_end_sound: Option<PathBuf>,
+
+    /// Sound file to play when all pomodoros are done
+    #[structopt(long, parse(from_os_str))]
+    done_sound: Option<PathBuf>,
+
+    /// Don't play any sound
+    #[structopt(long)]
+    mute: bool,
+
+    /// Play sounds even if the config mutes them
+    #[structopt(long, conflicts_with = "mute")]
+    no_mute: bool,
+
+    /// Show the timer on a single line instead of the full screen
+    #[structopt(long)]
+    compact: bool,
+
+    /// Use the full screen even if the config asks for a single line
+    #[structopt(long, conflicts_with = "compact")]
+    no_compact: bool,
+
+    /// What the pomodoros are spent on, shown in the status line and recorded in the history
+    #[structopt(short, long)]
+    task: Option<String>,
+
+    /// Pick each pomodoro's task from the unfinished items of this todo.txt file
+    #[structopt(long, parse(from_os_str))]
+    todo: Option<PathBuf>,
+
+    /// Count completed pomodoros in a pomo:N tag on the todo.txt item
+    #[structopt(long)]
+    pomo_tags: bool,
+
+    /// Leave the todo.txt items' pomo:N tags alone even if the config counts them
+    #[structopt(long, conflicts_with = "pomo_tags")]
+    no_pomo_tags: bool,
+
+    /// Send a desktop notification when an interval ends
+    #[structopt(long)]
+    notify: bool,
+
+    /// Don't send desktop notifications even if the config turns them on
+    #[structopt(long, conflicts_with = "notify")]
+    no_notify: bool,
+
+    /// Make time run this many times faster, e.g. 60 turns minutes into seconds. Such sessions
+    /// aren't recorded in the history
+    #[structopt(long)]
+    speed: Option<u32>,
+
+    /// Pick up the session that was cut short last time without asking
+    #[structopt(long)]
+    resume: bool,
+
+    /// Run through the whole session hands-free at 600x speed (unless --speed is given),
+    /// without recording it in the history
+    #[structopt(long)]
+    simulate: bool,
+
+    #[structopt(subcommand)]
+    cmd: Option<Command>,
+}
+
+#[derive(StructOpt)]
+enum Command {
+    /// Summarize the session history per day and per week
+    Stats {
+        /// First day to include (YYYY-MM-DD)
+        #[structopt(long)]
+        since: Option<NaiveDate>,
+
+        /// Last day to include (YYYY-MM-DD)
+        #[structopt(long)]
+        until: Option<NaiveDate>,
+
+        /// Print the summary as JSON
+        #[structopt(long)]
+        json: bool,
+    },
+    /// Run sessions in the background, controlled over a socket in $XDG_RUNTIME_DIR
+    Daemon,
+    /// Start a session in the daemon, or the next interval when one is waiting to start
+    Start,
+    /// Pause the daemon's running interval
+    Pause,
+    /// Resume the daemon's paused interval
+    Resume,
+    /// Pause or resume the daemon's running interval
+    Toggle,
+    /// Skip the daemon's running interval, or start the next one when it's waiting
+    Skip,
+    /// Stop the daemon's session
+    Stop,
+    /// Show what the daemon is doing
+    Status {
+        /// Fill in a template instead, e.g. "{icon} {remaining} #{count}". Placeholders: {icon},
+        /// {mode}, {remaining}, {remaining_secs}, {count}, {max_pomodoros}, {paused} and {task}
+        #[structopt(long)]
+        format: Option<String>,
+
+        /// Print the status as JSON
+        #[structopt(long, conflicts_with = "format")]
+        json: bool,
+    },
+    /// Keep a list of tasks and how many pomodoros they take
+    Task(TaskCommand),
+}
+
+#[derive(StructOpt)]
+enum TaskCommand {
+    /// Add a task to the list
+    Add {
+        name: String,
+
+        /// How many pomodoros it should take
+        #[structopt(short, long)]
+        estimate: Option<u32>,
+    },
+    /// List the unfinished tasks with their estimated and actual pomodoros
+    List {
+        /// Include the finished ones
+        #[structopt(short, long)]
+        all: bool,
+    },
+    /// Work on a task: the timer uses it when no --task is given
+    Start { id: u32 },
+    /// Mark a task finished, the active one unless an id is given
+    Done { id: Option<u32> },
+}
+
+// Exit codes of the commands talking to the daemon.
+const EXIT_ERROR: i32 = 1;
+const EXIT_NO_DAEMON: i32 = 2;
+const EXIT_REFUSED: i32 = 3;
+const EXIT_NO_SOCKET_DIR: i32 = 4;
+
+// Nothing is read when muted, and a file that rings on several transitions is read once.
+fn load_sounds(files: &config::SoundFiles, mute: bool) -> Sounds {
+    let mut loaded: HashMap<PathBuf, Sound> = HashMap::new();
+    let mut load = |path: &Option<PathBuf>| match path {
+        Some(path) if !mute => loaded
+            .entry(path.clone())
+            .or_insert_with(|| {
+                Sound::from_file(path).unwrap_or_else(|e| {
+                    eprintln!(
+                        "Could not use sound {}: {}. Using the gong instead.",
+                        path.display(),
+                        e
+                    );
+                    Sound::gong()
+                })
+            })
+            .clone(),
+        _ => Sound::gong(),
+    };
+    Sounds {
+        pomodoro_end: load(&files.pomodoro_end),
+        break_end: load(&files.break_end),
+        done: load(&files.done),
+    }
+}
+
+// Puts `message` on a line of its own, above the status line when there is one. Cursor tricks
+// would only garble the output when it isn't a terminal.
+fn warn(stdout: &mut impl Write, message: &str) {
+    if termion::is_tty(&io::stdout()) {
+        write!(stdout, "{}{}\r\n", termion::clear::CurrentLine, message).unwrap();
+    } else {
+        writeln!(stdout, "{}", message).unwrap();
+    }
+}
+
+// Rings the terminal bell instead when the sound can't be played, e.g. on a headless box.
+fn ring(stdout: &mut impl Write, sound: &Sound, mute: bool) {
+    if mute {
+        return;
+    }
+    if sound.play().is_err() && termion::is_tty(&io::stdout()) {
+        write!(stdout, "\x07").unwrap();
+    }
 }
 
-struct StateMachine {
-    pomodoro_count: u8,
-    break_count: u8,
-    max_pomodoros: u8,
-    mode: Mode,
+// The warning shows up whenever the notification fails, which may be a while after it was sent.
+fn notify(summary: &str, body: &str) {
+    notify::send(summary.to_string(), body.to_string(), |e| {
+        warn(
+            &mut io::stdout(),
+            &format!("Could not send notification: {}", e),
+        )
+    });
 }
 
-impl StateMachine {
-    fn new(max_pomodoros: u8) -> StateMachine {
-        StateMachine {
-            pomodoro_count: 1,
-            break_count: 1,
-            max_pomodoros,
-            mode: Mode::Pomodoro,
+fn run_hook(stdout: &mut impl Write, command: &Option<String>, timer: &Timer) {
+    let command = match command {
+        Some(command) => command,
+        None => return,
+    };
+
+    let context = hooks::Context {
+        mode: timer.mode().name(),
+        count: timer.state().count(),
+        remaining: timer.interval().remaining(),
+        task: timer.state().task(),
+    };
+    if let Err(e) = hooks::run(command, &context) {
+        warn(stdout, &format!("Could not run hook: {}", e));
+    }
+}
+
+// Counts a completed pomodoro towards the task list entry it was spent on, and in the todo.txt
+// item's pomo:N tag when asked to, if there are such.
+fn count_pomodoro(stdout: &mut impl Write, entry: &history::Entry, timer: &Timer, config: &Config) {
+    let task = match &entry.task {
+        Some(task) if config.history && entry.outcome == history::Outcome::Completed => task,
+        _ => return,
+    };
+    if let Some(id) = timer.state().task_id() {
+        // read it afresh: tasks may have been added since the session started
+        let result = tasks::load().and_then(|mut list| {
+            if list.count_pomodoro(id) {
+                tasks::save(&list)
+            } else {
+                Ok(())
+            }
+        });
+        if let Err(e) = result {
+            warn(stdout, &format!("Could not update the task list: {}", e));
         }
     }
 
-    fn next_state(&mut self) {
-        match self.mode {
-            Mode::EnteringPomodoro => self.mode = Mode::Pomodoro,
-            Mode::Pomodoro => {
-                if self.pomodoro_count == self.max_pomodoros {
-                    self.mode = Mode::End;
-                } else {
-                    self.mode = Mode::PomodoroEnded;
+    if let (Some(path), true) = (&config.todo.file, config.todo.pomo_tags) {
+        if let Err(e) = todo::count_pomodoro(path, task) {
+            warn(
+                stdout,
+                &format!("Could not update {}: {}", path.display(), e),
+            );
+        }
+    }
+}
+
+// Offers the todo.txt items to pick the next pomodoro's task from, when there's a file to read.
+fn pick_task(stdout: &mut impl Write, config: &Config, timer: &Timer) -> Option<todo::Picker> {
+    let path = config.todo.file.as_ref()?;
+    match todo::read(path) {
+        Ok(items) => todo::Picker::new(items, timer.state().task()),
+        Err(e) => {
+            warn(stdout, &format!("Could not read {}: {}", path.display(), e));
+            None
+        }
+    }
+}
+
+// The one-line view of the picker: the selected item and where it is in the list.
+fn picker_line(picker: &todo::Picker, keys: &config::Keys) -> String {
+    format!(
+        "Task for the next pomodoro ({}/{}, {}/{}, enter, {} done, esc keep, {} quit): {}",
+        picker.selected() + 1,
+        picker.items().len(),
+        keys.up,
+        keys.down,
+        keys.done,
+        keys.quit,
+        picker.item().line
+    )
+}
+
+fn record_history(stdout: &mut impl Write, entry: &history::Entry, config: &Config) {
+    if !config.history {
+        return;
+    }
+    if let Err(e) = history::append(entry) {
+        warn(stdout, &format!("Could not write history: {}", e));
+    }
+}
+
+// Whole seconds for a countdown, rounded up so it reaches 0 just as it runs out.
+fn secs(duration: Duration) -> u64 {
+    duration.as_secs() + u64::from(duration.subsec_nanos() > 0)
+}
+
+// Everything the binary does on top of the engine: history, sounds, notifications and hooks.
+fn handle_event(
+    stdout: &mut impl Write,
+    event: &TimerEvent,
+    timer: &Timer,
+    config: &Config,
+    sounds: &Sounds,
+) {
+    let hooks = &config.hooks;
+    let state = timer.state();
+    match event {
+        TimerEvent::PomodoroStarted => run_hook(stdout, &hooks.on_pomodoro_start, timer),
+        TimerEvent::PomodoroEnded(entry) => {
+            record_history(stdout, entry, config);
+            count_pomodoro(stdout, entry, timer, config);
+            run_hook(stdout, &hooks.on_pomodoro_end, timer);
+            // the last pomodoro rings as `Done` instead, and skipping needs no reminder
+            if timer.mode() != Mode::End && entry.outcome == history::Outcome::Completed {
+                ring(stdout, &sounds.pomodoro_end, config.mute);
+                if config.notify {
+                    let summary = format!("Pomodoro {} ended", state.pomodoro_count());
+                    let kind = if state.is_long_break_due() {
+                        "long break"
+                    } else {
+                        "break"
+                    };
+                    let body = match timer.auto_start_in() {
+                        Some(delay) => format!("Your {} starts in {}s.", kind, secs(delay)),
+                        None => format!("Press a key to start your {}.", kind),
+                    };
+                    notify(&summary, &body);
                 }
             }
-            Mode::PomodoroEnded => {
-                self.pomodoro_count += 1;
-                self.mode = Mode::EnteringBreak;
+        }
+        TimerEvent::BreakStarted { .. } => run_hook(stdout, &hooks.on_break_start, timer),
+        TimerEvent::BreakEnded(entry) => {
+            record_history(stdout, entry, config);
+            run_hook(stdout, &hooks.on_break_end, timer);
+            if entry.outcome != history::Outcome::Completed {
+                return;
+            }
+            ring(stdout, &sounds.break_end, config.mute);
+            if config.notify {
+                let summary = if entry.kind == history::Kind::LongBreak {
+                    format!("Long break {} ended", state.break_count())
+                } else {
+                    format!("Break {} ended", state.break_count())
+                };
+                let body = match timer.auto_start_in() {
+                    Some(delay) => format!(
+                        "Pomodoro {} starts in {}s.",
+                        state.pomodoro_count(),
+                        secs(delay)
+                    ),
+                    None => format!("Press a key to start pomodoro {}.", state.pomodoro_count()),
+                };
+                notify(&summary, &body);
+            }
+        }
+        TimerEvent::Restarted(entry) => record_history(stdout, entry, config),
+        TimerEvent::Suspended(_) => (),
+        TimerEvent::Paused => run_hook(stdout, &hooks.on_pause, timer),
+        TimerEvent::Resumed => run_hook(stdout, &hooks.on_resume, timer),
+        TimerEvent::Quit(entry) => {
+            if let Some(entry) = entry {
+                record_history(stdout, entry, config);
             }
-            Mode::EnteringBreak => self.mode = Mode::Break,
-            Mode::Break => self.mode = Mode::BreakEnded,
-            Mode::BreakEnded => {
-                self.break_count += 1;
-                self.mode = Mode::EnteringPomodoro;
+            run_hook(stdout, &hooks.on_quit, timer);
+        }
+        TimerEvent::Done => {
+            run_hook(stdout, &hooks.on_done, timer);
+            ring(stdout, &sounds.done, config.mute);
+            if config.notify {
+                let body = format!(
+                    "Pomodoro {} ended. Press a key to finish.",
+                    state.pomodoro_count()
+                );
+                notify("All pomodoros done", &body);
             }
-            Mode::End => (),
         }
     }
 }
 
-struct Interval {
-    elapsed: Duration,
-    duration: Duration,
+// The interruptions of the running pomodoro the way the technique marks them, e.g. " ''-".
+fn tally(timer: &Timer) -> String {
+    let interruptions = timer.interval().interruptions();
+    if interruptions.is_empty() {
+        return String::new();
+    }
+
+    let internal = interruptions
+        .iter()
+        .filter(|interruption| interruption.kind == InterruptionKind::Internal)
+        .count();
+    format!(
+        " {}{}",
+        "'".repeat(internal),
+        "-".repeat(interruptions.len() - internal)
+    )
 }
 
-impl Interval {
-    fn from_secs(secs: u64) -> Interval {
-        Interval {
-            elapsed: Duration::from_secs(0),
-            duration: Duration::from_secs(secs),
+// What the one-line view shows, and the prompt of the full-screen one.
+fn status_line(timer: &Timer, config: &Config, input: Option<&Input>) -> String {
+    let state = timer.state();
+    let task = match state.task() {
+        Some(task) => format!(" - {}", task),
+        None => String::new(),
+    };
+    let paused = if timer.is_paused() { " (paused)" } else { "" };
+    match timer.mode() {
+        _ if input.is_some() => {
+            let input = input.unwrap();
+            format!("{}: {}", input.prompt(), input.text())
         }
+        Mode::Pomodoro => format!(
+            "Pomodoro {}: {}{}{}{}",
+            state.pomodoro_count(),
+            timer.interval(),
+            paused,
+            tally(timer),
+            task,
+        ),
+        Mode::Break => format!(
+            "Break {}: {}{}",
+            state.break_count(),
+            timer.interval(),
+            paused,
+        ),
+        Mode::LongBreak => format!(
+            "Long break {}: {}{}",
+            state.break_count(),
+            timer.interval(),
+            paused,
+        ),
+        Mode::PomodoroEnded if timer.auto_start_in().is_some() => format!(
+            "Pomodoro ended. Break starts in {}s, press any key to hold.",
+            secs(timer.auto_start_in().unwrap()),
+        ),
+        Mode::PomodoroEnded => "Pomodoro ended. Press key to begin break.".to_string(),
+        Mode::BreakEnded | Mode::LongBreakEnded if timer.auto_start_in().is_some() => format!(
+            "Break ended. Pomodoro starts in {}s, press {} to set the task or any other key to hold{}.",
+            secs(timer.auto_start_in().unwrap()),
+            config.keys.task,
+            task,
+        ),
+        Mode::BreakEnded | Mode::LongBreakEnded => format!(
+            "Break ended. Press {} to set the task or any other key to begin a new pomodoro{}.",
+            config.keys.task,
+            task,
+        ),
+        Mode::End => "Done. Press any key to end.".to_string(),
+        _ => String::new(),
     }
+}
 
-    fn has_ended(&self) -> bool {
-        self.elapsed >= self.duration
+impl Opt {
+    fn apply(&self, config: &mut Config) {
+        if let Some(pomodoro_duration) = self.pomodoro_duration {
+            config.pomodoro_duration = pomodoro_duration;
+        }
+        if let Some(break_duration) = self.break_duration {
+            config.break_duration = break_duration;
+        }
+        if let Some(max_pomodoros) = self.max_pomodoros {
+            config.max_pomodoros = max_pomodoros;
+        }
+        if let Some(long_break_duration) = self.long_break_duration {
+            config.long_break_duration = long_break_duration;
+        }
+        if let Some(long_break_every) = self.long_break_every {
+            config.long_break_every = long_break_every;
+        }
+        if self.auto_start_breaks {
+            config.auto_start_breaks = true;
+        }
+        if self.no_auto_start_breaks {
+            config.auto_start_breaks = false;
+        }
+        if self.auto_start_pomodoros {
+            config.auto_start_pomodoros = true;
+        }
+        if self.no_auto_start_pomodoros {
+            config.auto_start_pomodoros = false;
+        }
+        if let Some(auto_start_delay) = self.auto_start_delay {
+            config.auto_start_delay = auto_start_delay;
+        }
+        if let Some(on_suspend) = self.on_suspend {
+            config.on_suspend = on_suspend;
+        }
+        if let Some(adjust_by) = self.adjust_by {
+            config.adjust_by = adjust_by;
+        }
+        if let Some(sound) = &self.sound {
+            config.sounds.pomodoro_end = Some(sound.clone());
+            config.sounds.break_end = Some(sound.clone());
+            config.sounds.done = Some(sound.clone());
+        }
+        if let Some(sound) = &self.pomodoro_end_sound {
+            config.sounds.pomodoro_end = Some(sound.clone());
+        }
+        if let Some(sound) = &self.break_end_sound {
+            config.sounds.break_end = Some(sound.clone());
+        }
+        if let Some(sound) = &self.done_sound {
+            config.sounds.done = Some(sound.clone());
+        }
+        if self.mute {
+            config.mute = true;
+        }
+        if self.no_mute {
+            config.mute = false;
+        }
+        if self.notify {
+            config.notify = true;
+        }
+        if self.no_notify {
+            config.notify = false;
+        }
+        if self.compact {
+            config.compact = true;
+        }
+        if self.no_compact {
+            config.compact = false;
+        }
+        if let Some(todo) = &self.todo {
+            config.todo.file = Some(todo.clone());
+        }
+        if self.pomo_tags {
+            config.todo.pomo_tags = true;
+        }
+        if self.no_pomo_tags {
+            config.todo.pomo_tags = false;
+        }
+        // sped-up sessions weren't really worked, and their timestamps run ahead of the clock
+        if self.simulate || self.speed.is_some_and(|speed| speed > 1) {
+            config.history = false;
+        }
     }
 }
 
-impl SubAssign<Duration> for Interval {
-    #[allow(clippy::suspicious_op_assign_impl)]
-    fn sub_assign(&mut self, rhs: Duration) {
-        // count up on `elapsed` because Duration can't be negative
-        // per https://rust-lang-nursery.github.io/rust-cookbook/datetime/duration.html#measure-the-elapsed-time-between-two-code-sections
-        self.elapsed += rhs;
+fn print_stats(since: Option<NaiveDate>, until: Option<NaiveDate>, json: bool) {
+    let entries = match history::read(|e| eprintln!("Skipping a bad history entry: {}", e)) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("Could not read history: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let report = stats::summarize(&entries, since, until);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    } else {
+        print!("{}", report);
     }
 }
 
-impl Display for Interval {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        let secs = if self.elapsed >= self.duration {
-            0
+// A new session on `task`, or on the task list's active task when none is given. Not having a
+// task list is no reason to stop.
+fn new_timer(schedule: Schedule, task: Option<String>, speed: u32) -> Timer {
+    let mut timer = Timer::with_clock(schedule, task.clone(), SystemClock::with_speed(speed));
+    if task.is_none() {
+        match tasks::load() {
+            Ok(list) => {
+                if let Some(task) = list.active() {
+                    timer.set_listed_task(task);
+                }
+            }
+            Err(e) => eprintln!("Could not read the task list: {}", e),
+        }
+    }
+    timer
+}
+
+fn run_task_command(command: &TaskCommand) {
+    let mut list = match tasks::load() {
+        Ok(list) => list,
+        Err(e) => {
+            eprintln!("Could not read the task list: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let changed = match command {
+        TaskCommand::Add { name, estimate } => {
+            let id = list.add(name.clone(), *estimate);
+            println!("Added task {}", id);
+            true
+        }
+        TaskCommand::List { all } => {
+            print_tasks(&list, *all);
+            false
+        }
+        TaskCommand::Start { id } => {
+            if !list.start(*id) {
+                eprintln!("No unfinished task {}", id);
+                std::process::exit(1);
+            }
+            true
+        }
+        TaskCommand::Done { id } => {
+            let id = match id.or_else(|| list.active().map(|task| task.id)) {
+                Some(id) => id,
+                None => {
+                    eprintln!("No unfinished tasks");
+                    std::process::exit(1);
+                }
+            };
+            if !list.finish(id) {
+                eprintln!("No task {}", id);
+                std::process::exit(1);
+            }
+            true
+        }
+    };
+
+    if changed {
+        if let Err(e) = tasks::save(&list) {
+            eprintln!("Could not save the task list: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+// One task per line with its estimated and actual pomodoros, the active one marked with `*`.
+fn print_tasks(list: &tasks::TaskList, all: bool) {
+    let active = list.active().map(|task| task.id);
+    println!("   {:>4} {:>4} {:>4}  Task", "Id", "Est", "Act");
+    for task in list.tasks().iter().filter(|task| all || !task.done) {
+        let mark = if task.done {
+            "✔"
+        } else if Some(task.id) == active {
+            "*"
         } else {
-            (self.duration - self.elapsed).as_secs()
+            " "
         };
+        let estimate = match task.estimate {
+            Some(estimate) => estimate.to_string(),
+            None => "-".to_string(),
+        };
+        println!(
+            " {} {:>4} {:>4} {:>4}  {}",
+            mark, task.id, estimate, task.actual, task.name
+        );
+    }
+}
+
+// Sends `request` to the daemon, exiting with a code telling what went wrong if it fails.
+// Returns the status after carrying it out.
+fn control(request: Request) -> Status {
+    let path = match control::socket_path() {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("No place for the daemon's socket: {}", e);
+            std::process::exit(EXIT_NO_SOCKET_DIR);
+        }
+    };
+    let response = match control::send(&path, &request) {
+        Ok(response) => response,
+        Err(e)
+            if e.kind() == io::ErrorKind::NotFound
+                || e.kind() == io::ErrorKind::ConnectionRefused =>
+        {
+            eprintln!("No timer running: start one with `pomodoro daemon`");
+            std::process::exit(EXIT_NO_DAEMON);
+        }
+        Err(e) => {
+            eprintln!("Could not talk to the daemon: {}", e);
+            std::process::exit(EXIT_ERROR);
+        }
+    };
+
+    if !response.ok {
+        eprintln!("{}", response.error.unwrap_or_default());
+        std::process::exit(EXIT_REFUSED);
+    }
+    match response.status {
+        Some(status) => status,
+        None => {
+            eprintln!("Could not talk to the daemon: no status in its answer");
+            std::process::exit(EXIT_ERROR);
+        }
+    }
+}
 
-        write!(f, "{:02}:{:02}", secs / 60, secs % 60)
+fn print_status(format: Option<&str>, json: bool) {
+    let status = control(Request::Status);
+    if json {
+        println!("{}", serde_json::to_string(&status).unwrap());
+    } else if let Some(format) = format {
+        println!("{}", status.render(format));
+    } else {
+        println!("{}", status);
     }
 }
 
-// TODO: add more descriptions
-#[derive(StructOpt)]
-#[structopt(name = "pomodoro")]
-struct Opt {
-    #[structopt(short, long, default_value = "25")]
-    pomodoro_duration: u8,
+// How often the running session is saved, on top of every transition.
+const CHECKPOINT_EVERY: Duration = Duration::from_secs(5);
+
+fn save_checkpoint(stdout: &mut impl Write, timer: &Timer) {
+    if let Err(e) = checkpoint::save(&timer.checkpoint()) {
+        warn(stdout, &format!("Could not save the session: {}", e));
+    }
+}
 
-    #[structopt(short, long, default_value = "4")]
-    break_duration: u8,
+fn clear_checkpoint(stdout: &mut impl Write) {
+    if let Err(e) = checkpoint::clear() {
+        warn(stdout, &format!("Could not clear the saved session: {}", e));
+    }
+}
 
-    #[structopt(short, long, default_value = "4")]
-    max_pomodoros: u8,
+// Where a restored session picks up.
+fn resume_point(timer: &Timer) -> String {
+    let state = timer.state();
+    match timer.mode() {
+        Mode::Pomodoro => format!(
+            "pomodoro {} of {} with {} left",
+            state.pomodoro_count(),
+            state.max_pomodoros(),
+            timer.interval()
+        ),
+        Mode::Break | Mode::LongBreak => {
+            format!(
+                "break {} with {} left",
+                state.break_count(),
+                timer.interval()
+            )
+        }
+        Mode::PomodoroEnded => format!("the end of pomodoro {}", state.pomodoro_count()),
+        _ => format!("the end of break {}", state.break_count()),
+    }
 }
 
-// include_bytes! adds the song to the binary
-const GONG: &[u8] = include_bytes!("indian-gong.mp3");
+// The session a crash or a closed terminal cut short, if the user wants it back. `--resume`
+// takes it without asking; otherwise we ask when there's a terminal to ask on.
+fn unfinished_session(
+    schedule: &Schedule,
+    speed: u32,
+    resume: bool,
+    interactive: bool,
+) -> Option<Timer> {
+    let checkpoint = match checkpoint::load() {
+        Ok(checkpoint) => checkpoint?,
+        Err(e) => {
+            eprintln!("Could not read the unfinished session: {}", e);
+            return None;
+        }
+    };
+    let saved_at = checkpoint.saved_at();
+    let timer = Timer::restore(schedule.clone(), checkpoint, SystemClock::with_speed(speed));
+    if timer.mode() == Mode::End {
+        return None;
+    }
+    if resume {
+        return Some(timer);
+    }
+    if !interactive {
+        return None;
+    }
+
+    print!(
+        "Resume the session left at {} on {}? [Y/n] ",
+        resume_point(&timer),
+        saved_at.format("%a %H:%M")
+    );
+    io::stdout().flush().unwrap();
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer).ok()?;
+    match answer.trim() {
+        "" | "y" | "Y" | "yes" => Some(timer),
+        // so it isn't offered again if this session ends before it's saved
+        _ => {
+            if let Err(e) = checkpoint::clear() {
+                eprintln!("Could not clear the saved session: {}", e);
+            }
+            None
+        }
+    }
+}
 
-// TODO: add option to play synchronously when ending
-fn play_sound() {
-    let device = rodio::default_output_device().unwrap();
-    let cursor = Cursor::new(GONG);
-    let source = rodio::Decoder::new(cursor).unwrap();
-    let source = source.take_duration(Duration::from_secs(20)); // there's something off about the duration
-    rodio::play_raw(&device, source.convert_samples());
+// What a transition looks like in the plain output, if it's worth a line.
+fn describe(event: &TimerEvent, timer: &Timer) -> Option<String> {
+    let state = timer.state();
+    let task = match state.task() {
+        Some(task) => format!(" - {}", task),
+        None => String::new(),
+    };
+    match event {
+        TimerEvent::PomodoroStarted => Some(format!(
+            "Pomodoro {} started ({}){}",
+            state.pomodoro_count(),
+            timer.interval(),
+            task
+        )),
+        TimerEvent::PomodoroEnded(_) => Some(format!("Pomodoro {} ended", state.pomodoro_count())),
+        TimerEvent::BreakStarted { long: true } => Some(format!(
+            "Long break {} started ({})",
+            state.break_count(),
+            timer.interval()
+        )),
+        TimerEvent::BreakStarted { long: false } => Some(format!(
+            "Break {} started ({})",
+            state.break_count(),
+            timer.interval()
+        )),
+        TimerEvent::BreakEnded(_) => Some(format!("Break {} ended", state.break_count())),
+        TimerEvent::Done => Some("Done".to_string()),
+        TimerEvent::Quit(_) => Some("Quit".to_string()),
+        TimerEvent::Suspended(gap) => Some(format!(
+            "Suspended for {}",
+            humantime::format_duration(Duration::from_secs(gap.as_secs()))
+        )),
+        TimerEvent::Restarted(entry) if entry.outcome == history::Outcome::Voided => Some(format!(
+            "Pomodoro {} voided, starting over",
+            state.pomodoro_count()
+        )),
+        _ => None,
+    }
+}
+
+// Runs the session without a terminal: no keys, no cursor tricks, and every interval starts
+// as soon as the previous one ends.
+// Set on SIGINT and SIGTERM, the only ways to stop the plain output.
+static STOPPED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn stop(_: libc::c_int) {
+    STOPPED.store(true, Ordering::SeqCst);
+}
+
+fn run_plain(mut timer: Timer, config: &Config, sounds: &Sounds, speed: u32, checkpoints: bool) {
+    // stop the way the quit key does, so the quit is recorded and the session isn't offered
+    // again
+    let handler = stop as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    unsafe {
+        libc::signal(libc::SIGINT, handler);
+        libc::signal(libc::SIGTERM, handler);
+    }
+    let mut stdout = io::stdout();
+    let tick_rate = (Duration::from_millis(500) / speed).max(Duration::from_millis(10));
+    let mut last_checkpoint: Option<Instant> = None;
+    // there's no key to resume a session that was saved paused
+    if timer.is_paused() {
+        if let Some(event) = timer.toggle_pause() {
+            handle_event(&mut stdout, &event, &timer, config, sounds);
+        }
+    }
+    loop {
+        let stopped = STOPPED.load(Ordering::SeqCst);
+        let events = if stopped {
+            vec![timer.quit()]
+        } else {
+            timer.tick()
+        };
+        for event in &events {
+            if let Some(line) = describe(event, &timer) {
+                let time = timer.clock().wall().format("%H:%M:%S");
+                writeln!(stdout, "[{}] {}", time, line).unwrap();
+            }
+            handle_event(&mut stdout, event, &timer, config, sounds);
+        }
+        stdout.flush().unwrap();
+
+        if stopped || timer.mode() == Mode::End {
+            if checkpoints {
+                clear_checkpoint(&mut stdout);
+            }
+            notify::wait();
+            return;
+        }
+        if checkpoints
+            && (!events.is_empty()
+                || last_checkpoint.is_none_or(|at| at.elapsed() >= CHECKPOINT_EVERY))
+        {
+            save_checkpoint(&mut stdout, &timer);
+            last_checkpoint = Some(Instant::now());
+        }
+        timer.acknowledge();
+        thread::sleep(tick_rate);
+    }
 }
 
 fn main() {
     let opt = Opt::from_args();
 
-    let break_duration: u64 = opt.break_duration as u64 * 60;
-    let pomodoro_duration: u64 = opt.pomodoro_duration as u64 * 60;
-    let max_pomodoros = opt.max_pomodoros;
+    let request = match &opt.cmd {
+        Some(Command::Stats { since, until, json }) => return print_stats(*since, *until, *json),
+        Some(Command::Status { format, json }) => return print_status(format.as_deref(), *json),
+        Some(Command::Task(command)) => return run_task_command(command),
+        Some(Command::Start) => Some(Request::Start),
+        Some(Command::Pause) => Some(Request::Pause),
+        Some(Command::Resume) => Some(Request::Resume),
+        Some(Command::Toggle) => Some(Request::Toggle),
+        Some(Command::Skip) => Some(Request::Skip),
+        Some(Command::Stop) => Some(Request::Stop),
+        Some(Command::Daemon) | None => None,
+    };
+    if let Some(request) = request {
+        control(request);
+        return;
+    }
+
+    let mut config = match config::load(opt.config.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Could not load config: {}", e);
+            std::process::exit(1);
+        }
+    };
+    opt.apply(&mut config);
+
+    let schedule = Schedule {
+        pomodoro: config.pomodoro_duration,
+        short_break: config.break_duration,
+        long_break: config.long_break_duration,
+        long_break_every: config.long_break_every,
+        max_pomodoros: config.max_pomodoros,
+        auto_start_breaks: config.auto_start_breaks,
+        auto_start_pomodoros: config.auto_start_pomodoros,
+        auto_start_delay: config.auto_start_delay,
+        on_suspend: config.on_suspend,
+    };
+    let sounds = load_sounds(&config.sounds, config.mute);
+    let speed = match (opt.speed, opt.simulate) {
+        (Some(speed), _) => speed.max(1),
+        (None, true) => 600,
+        (None, false) => 1,
+    };
+
+    if let Some(Command::Daemon) = opt.cmd {
+        if let Err(e) = daemon::run(&config, schedule, &sounds, opt.task.clone(), speed) {
+            eprintln!("Could not run daemon: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // scripts, cron jobs and pipes get one line per transition instead of the interactive view
+    let interactive = termion::is_tty(&io::stdin()) && termion::is_tty(&io::stdout());
+    // a sped-up session leaves the real one alone, as it does the history: resumed, it would go
+    // on at real speed and count as worked
+    let checkpoints = speed == 1;
+    let unfinished = if checkpoints {
+        unfinished_session(&schedule, speed, opt.resume, interactive)
+    } else {
+        None
+    };
+    let resumed = unfinished.is_some();
+    let mut timer = unfinished.unwrap_or_else(|| new_timer(schedule, opt.task.clone(), speed));
+    if !interactive {
+        if resumed {
+            let time = timer.clock().wall().format("%H:%M:%S");
+            println!("[{}] Resuming at {}", time, resume_point(&timer));
+        }
+        return run_plain(timer, &config, &sounds, speed, checkpoints);
+    }
+
+    // the first pomodoro waits for its task to be picked, unless it was given or resumed
+    let mut picker = if opt.task.is_none() && !resumed && !opt.simulate {
+        pick_task(&mut io::stdout(), &config, &timer)
+    } else {
+        None
+    };
 
     // We create a channel for communication. We can have as many `tx`s as we want, but
     // only a single `rx`.
@@ -160,144 +1100,259 @@ fn main() {
     });
 
     // NB: stdout must be in raw mode for individual keypresses to work
-    let mut stdout = io::stdout().into_raw_mode().unwrap();
-
-    write!(stdout, "{}", termion::cursor::Hide).unwrap();
+    // HideCursor shows the cursor again when dropped, even if we panic, and so does
+    // AlternateScreen with the screen as it was
+    let stdout = HideCursor::from(io::stdout().into_raw_mode().unwrap());
+    let mut stdout: Box<dyn Write> = if config.compact {
+        Box::new(stdout)
+    } else {
+        Box::new(AlternateScreen::from(stdout))
+    };
 
-    // TODO: write tests
-    let mut state_machine = StateMachine::new(max_pomodoros);
-    let mut interval = Interval::from_secs(pomodoro_duration);
-    let mut paused = false;
-    let mut acked = false;
+    // check for keys more often when time runs faster so the countdown still moves smoothly
+    let tick_rate = (Duration::from_millis(500) / speed).max(Duration::from_millis(10));
+    // the line being typed in, if any
+    let mut input: Option<Input> = None;
+    // something the full screen shows until the next key press, since it redraws over warnings
+    let mut notice: Option<&str> = None;
+    let mut last_checkpoint: Option<Instant> = None;
+    let mut last_mode = timer.mode();
     loop {
-        let start = Instant::now();
-        match rx.recv_timeout(Duration::from_millis(500)) {
-            Ok(Event::Key(_)) if state_machine.mode == Mode::PomodoroEnded => {
-                acked = true;
-            }
-            Ok(Event::Key(_)) if state_machine.mode == Mode::BreakEnded => {
-                acked = true;
-            }
-            Ok(Event::Key(_)) if state_machine.mode == Mode::End => break,
-            Ok(Event::Key(Key::Char('q'))) | Ok(Event::Key(Key::Ctrl('c'))) => break,
-            Ok(Event::Key(Key::Char('p'))) => paused = !paused,
-            Err(RecvTimeoutError::Disconnected) => {
-                write!(
-                    stdout,
-                    "{}System error. Shutting down.\r\n",
-                    termion::clear::CurrentLine,
-                )
-                .unwrap();
+        // the session starts once the first pomodoro's task is picked
+        let events = if picker.is_some() && timer.mode() == Mode::EnteringPomodoro {
+            Vec::new()
+        } else {
+            timer.tick()
+        };
+        for event in &events {
+            handle_event(&mut stdout, event, &timer, &config, &sounds);
+        }
+        if timer.mode() != last_mode {
+            last_mode = timer.mode();
+            // pick the next pomodoro's task when the break is over
+            if !opt.simulate && (last_mode == Mode::BreakEnded || last_mode == Mode::LongBreakEnded)
+            {
+                picker = pick_task(&mut stdout, &config, &timer);
+                if picker.is_some() {
+                    timer.hold();
+                }
             }
-            _ => (),
+        }
+        // the session hasn't started while the first pomodoro's task is picked, so there's
+        // nothing to save yet
+        let picking = picker.is_some() && timer.mode() == Mode::EnteringPomodoro;
+        if checkpoints
+            && !picking
+            && (!events.is_empty()
+                || last_checkpoint.is_none_or(|at| at.elapsed() >= CHECKPOINT_EVERY))
+        {
+            save_checkpoint(&mut stdout, &timer);
+            last_checkpoint = Some(Instant::now());
         }
 
-        if !paused && (state_machine.mode == Mode::Pomodoro || state_machine.mode == Mode::Break) {
-            interval -= start.elapsed();
+        // TODO: control the rate of writing independently from tick?
+        let line = match &picker {
+            Some(picker) => picker_line(picker, &config.keys),
+            None => status_line(&timer, &config, input.as_ref()),
+        };
+        if config.compact {
+            // \r\n: https://stackoverflow.com/a/48497050
+            // In raw_mode \n keep the cursor at the same column; \r is needed to put the cursor
+            // at the beginning of the line.
+            write!(stdout, "{}{}\r", termion::clear::CurrentLine, line).unwrap();
+        } else if let Some(picker) = &picker {
+            screen::draw_picker(&mut stdout, picker, &config.keys);
+        } else {
+            // the full screen shows the countdown itself, the line is only needed for prompts
+            let message = match notice {
+                Some(notice) if input.is_none() => Some(notice),
+                _ if input.is_some() || !timer.mode().is_running() => Some(line.as_str()),
+                _ => None,
+            };
+            screen::draw(&mut stdout, &timer, &config.keys, message);
         }
+        stdout.flush().unwrap();
 
-        // The nice thing about using match with Enums in Rust is you get
-        // exhaustive match checking. This ensures you're covering all cases.
-        match state_machine.mode {
-            Mode::EnteringPomodoro => {
-                interval = Interval::from_secs(pomodoro_duration);
-                state_machine.next_state();
+        if opt.simulate {
+            if timer.mode() == Mode::End {
+                break;
             }
-            Mode::Pomodoro if interval.has_ended() => {
-                play_sound();
-                state_machine.next_state();
+            timer.acknowledge();
+        }
+
+        let received = rx.recv_timeout(tick_rate);
+        if received.is_ok() {
+            notice = None;
+        }
+        match received {
+            Ok(Event::Key(key)) if picker.is_some() => {
+                let list = picker.as_mut().unwrap();
+                match key {
+                    Key::Up => list.up(),
+                    Key::Down => list.down(),
+                    Key::Char(c) if c == config.keys.up => list.up(),
+                    Key::Char(c) if c == config.keys.down => list.down(),
+                    // and start on it, as the first pomodoro does
+                    Key::Char('\n') => {
+                        timer.set_task(Some(list.item().task.clone()));
+                        timer.acknowledge();
+                        picker = None;
+                    }
+                    // done already: mark it so in the file and pick another
+                    Key::Char(c) if c == config.keys.done => {
+                        let path = config.todo.file.as_ref().unwrap();
+                        if let Err(e) = todo::complete(path, &list.item().task) {
+                            warn(
+                                &mut stdout,
+                                &format!("Could not update {}: {}", path.display(), e),
+                            );
+                        } else if !list.remove() {
+                            timer.release();
+                            picker = None;
+                        }
+                    }
+                    // keep the task as it is, and go on as if there was no picker
+                    Key::Esc => {
+                        timer.release();
+                        picker = None;
+                    }
+                    // raw mode leaves no other way out
+                    key if key == Key::Char(config.keys.quit) || key == Key::Ctrl('c') => {
+                        let event = timer.quit();
+                        handle_event(&mut stdout, &event, &timer, &config, &sounds);
+                        break;
+                    }
+                    _ => (),
+                }
             }
-            Mode::PomodoroEnded if acked => {
-                acked = false;
-                state_machine.next_state();
+            Ok(Event::Key(Key::Char('\n'))) if input.is_some() => match input.take().unwrap() {
+                Input::Task(task) => {
+                    let task = task.trim();
+                    timer.set_task(if task.is_empty() {
+                        None
+                    } else {
+                        Some(task.to_string())
+                    });
+                }
+                Input::Note(note) => {
+                    let note = note.trim();
+                    // the pomodoro may have run out or started over while it was typed in
+                    if !note.is_empty() && !timer.note_interruption(note.to_string()) {
+                        let dropped =
+                            "The pomodoro ended before the note was in, so it wasn't recorded.";
+                        if config.compact {
+                            warn(&mut stdout, dropped);
+                        } else {
+                            notice = Some(dropped);
+                        }
+                    }
+                }
+                Input::Reason(reason) => {
+                    let reason = reason.trim();
+                    let reason = if reason.is_empty() {
+                        None
+                    } else {
+                        Some(reason.to_string())
+                    };
+                    // the pomodoro may have run out while the reason was typed in, too
+                    match timer.void(reason) {
+                        Some(event) => handle_event(&mut stdout, &event, &timer, &config, &sounds),
+                        None => {
+                            let dropped =
+                                "The pomodoro ended before it could be voided, so it counts.";
+                            if config.compact {
+                                warn(&mut stdout, dropped);
+                            } else {
+                                notice = Some(dropped);
+                            }
+                        }
+                    }
+                }
+            },
+            Ok(Event::Key(Key::Esc)) if input.is_some() => input = None,
+            Ok(Event::Key(key)) if input.is_some() => {
+                if let Some(input) = input.as_mut() {
+                    match key {
+                        Key::Backspace => {
+                            input.text_mut().pop();
+                        }
+                        Key::Char(c) => input.text_mut().push(c),
+                        _ => (),
+                    }
+                }
             }
-            Mode::EnteringBreak => {
-                interval = Interval::from_secs(break_duration);
-                state_machine.next_state();
+            Ok(Event::Key(Key::Char(c)))
+                if c == config.keys.task
+                    && (timer.mode() == Mode::BreakEnded
+                        || timer.mode() == Mode::LongBreakEnded) =>
+            {
+                // don't start the pomodoro while the task is being typed in
+                timer.hold();
+                input = Some(Input::Task(
+                    timer.state().task().unwrap_or_default().to_string(),
+                ));
             }
-            Mode::Break if interval.has_ended() => {
-                play_sound();
-                state_machine.next_state();
+            Ok(Event::Key(Key::Char(c)))
+                if (c == config.keys.internal_interruption
+                    || c == config.keys.external_interruption)
+                    && timer.mode() == Mode::Pomodoro =>
+            {
+                let kind = if c == config.keys.internal_interruption {
+                    InterruptionKind::Internal
+                } else {
+                    InterruptionKind::External
+                };
+                timer.interrupt(kind);
+                input = Some(Input::Note(String::new()));
             }
-            Mode::BreakEnded if acked => {
-                acked = false;
-                state_machine.next_state();
+            Ok(Event::Key(Key::Char(c)))
+                if c == config.keys.void && timer.mode() == Mode::Pomodoro =>
+            {
+                input = Some(Input::Reason(String::new()))
             }
-            _ => (),
-        }
-
-        // TODO: control the rate of writing independently from tick?
-        // \r\n: https://stackoverflow.com/a/48497050
-        // In raw_mode \n keep the cursor at the same column; \r is needed to put the cursor at the
-        // beginning of the line.
-        match state_machine.mode {
-            Mode::Pomodoro => {
-                if paused {
-                    write!(
-                        stdout,
-                        "{}Pomodoro {}: {} (paused)\r",
-                        termion::clear::CurrentLine,
-                        state_machine.pomodoro_count,
-                        interval,
-                    )
-                    .unwrap();
-                } else {
-                    write!(
-                        stdout,
-                        "{}Pomodoro {}: {}\r",
-                        termion::clear::CurrentLine,
-                        state_machine.pomodoro_count,
-                        interval,
-                    )
-                    .unwrap();
+            Ok(Event::Key(_)) if timer.auto_start_in().is_some() => timer.hold(),
+            Ok(Event::Key(_)) if timer.mode().is_waiting() => timer.acknowledge(),
+            Ok(Event::Key(_)) if timer.mode() == Mode::End => break,
+            Ok(Event::Key(key)) if key == Key::Char(config.keys.quit) || key == Key::Ctrl('c') => {
+                let event = timer.quit();
+                handle_event(&mut stdout, &event, &timer, &config, &sounds);
+                break;
+            }
+            Ok(Event::Key(Key::Char(c))) if c == config.keys.pause => {
+                if let Some(event) = timer.toggle_pause() {
+                    handle_event(&mut stdout, &event, &timer, &config, &sounds);
                 }
             }
-            Mode::Break => {
-                if paused {
-                    write!(
-                        stdout,
-                        "{}Break {}: {} (paused)\r",
-                        termion::clear::CurrentLine,
-                        state_machine.break_count,
-                        interval,
-                    )
-                    .unwrap();
-                } else {
-                    write!(
-                        stdout,
-                        "{}Break {}: {}\r",
-                        termion::clear::CurrentLine,
-                        state_machine.break_count,
-                        interval,
-                    )
-                    .unwrap();
+            Ok(Event::Key(Key::Char(c))) if c == config.keys.skip => {
+                for event in timer.skip() {
+                    handle_event(&mut stdout, &event, &timer, &config, &sounds);
                 }
             }
-            Mode::PomodoroEnded => write!(
-                stdout,
-                "{}Pomodoro ended. Press key to begin break.\r",
-                termion::clear::CurrentLine,
-            )
-            .unwrap(),
-            Mode::BreakEnded => write!(
-                stdout,
-                "{}Break ended. Press key to begin a new pomodoro.\r",
-                termion::clear::CurrentLine,
-            )
-            .unwrap(),
-            Mode::End => {
+            Ok(Event::Key(Key::Char(c))) if c == config.keys.restart => {
+                if let Some(event) = timer.restart() {
+                    handle_event(&mut stdout, &event, &timer, &config, &sounds);
+                }
+            }
+            Ok(Event::Key(Key::Char(c))) if c == config.keys.extend => {
+                timer.extend(config.adjust_by)
+            }
+            Ok(Event::Key(Key::Char(c))) if c == config.keys.shorten => {
+                timer.shorten(config.adjust_by)
+            }
+            Err(RecvTimeoutError::Disconnected) => {
                 write!(
                     stdout,
-                    "{}Done. Press any key to end.\r",
-                    termion::clear::CurrentLine
+                    "{}System error. Shutting down.\r\n",
+                    termion::clear::CurrentLine,
                 )
                 .unwrap();
             }
             _ => (),
         }
-        stdout.flush().unwrap();
     }
 
-    write!(stdout, "{}", termion::cursor::Show).unwrap();
-    stdout.flush().unwrap();
+    if checkpoints {
+        clear_checkpoint(&mut stdout);
+    }
+    notify::wait();
 }
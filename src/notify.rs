@@ -0,0 +1,42 @@
+use std::sync::Mutex;
+use std::thread;
+use std::thread::JoinHandle;
+
+use notify_rust::Notification;
+
+// The notifications still on their way.
+static SENDING: Mutex<Vec<JoinHandle<()>>> = Mutex::new(Vec::new());
+
+// Sends a freedesktop notification over the D-Bus session bus found in
+// $DBUS_SESSION_BUS_ADDRESS, so any notification daemon (or a stand-in listening on a private
+// bus) will pick it up. It's sent from a thread of its own, like hooks are run, so a slow bus
+// never holds up the timer; `failed` gets the error if it doesn't go through.
+pub fn send(
+    summary: String,
+    body: String,
+    failed: impl FnOnce(notify_rust::error::Error) + Send + 'static,
+) {
+    let handle = thread::spawn(move || {
+        let shown = Notification::new()
+            .appname("pomodoro")
+            .summary(&summary)
+            .body(&body)
+            .show();
+        if let Err(e) = shown {
+            failed(e);
+        }
+    });
+
+    let mut sending = SENDING.lock().unwrap();
+    sending.retain(|handle| !handle.is_finished());
+    sending.push(handle);
+}
+
+// Waits for the notifications still on their way, so that the last ones of a session aren't
+// lost when the timer exits right after.
+pub fn wait() {
+    let sending = std::mem::take(&mut *SENDING.lock().unwrap());
+    for handle in sending {
+        let _ = handle.join();
+    }
+}
@@ -0,0 +1,72 @@
+//! How long a session's intervals are and how many there are.
+
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// What to do with the time the system spent suspended while an interval ran.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum SuspendPolicy {
+    /// Count it against the interval, as if it kept running.
+    Count,
+    /// Count it as paused time and leave the interval paused.
+    Pause,
+    /// Throw the pomodoro away and start it over. Breaks count the time instead.
+    Void,
+}
+
+impl FromStr for SuspendPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<SuspendPolicy, String> {
+        match s {
+            "count" => Ok(SuspendPolicy::Count),
+            "pause" => Ok(SuspendPolicy::Pause),
+            "void" => Ok(SuspendPolicy::Void),
+            _ => Err(format!("expected count, pause or void, not {}", s)),
+        }
+    }
+}
+
+/// The layout of a session. The default is 4 pomodoros of 25 minutes with 4 minute breaks and
+/// a 15 minute long break after every 4th pomodoro. Nothing starts without an acknowledgement
+/// unless auto-start is turned on.
+#[derive(Clone, Debug)]
+pub struct Schedule {
+    /// How long a pomodoro is.
+    pub pomodoro: Duration,
+    /// How long a regular break is.
+    pub short_break: Duration,
+    /// How long a long break is.
+    pub long_break: Duration,
+    /// Take a long break after this many pomodoros, 0 disables long breaks.
+    pub long_break_every: u8,
+    /// How many pomodoros the session has.
+    pub max_pomodoros: u8,
+    /// Start breaks on their own once a pomodoro ends.
+    pub auto_start_breaks: bool,
+    /// Start pomodoros on their own once a break ends.
+    pub auto_start_pomodoros: bool,
+    /// How long to wait before starting on its own, giving the user a chance to hold.
+    pub auto_start_delay: Duration,
+    /// What to do when the system was suspended while an interval ran.
+    pub on_suspend: SuspendPolicy,
+}
+
+impl Default for Schedule {
+    fn default() -> Schedule {
+        Schedule {
+            pomodoro: Duration::from_secs(25 * 60),
+            short_break: Duration::from_secs(4 * 60),
+            long_break: Duration::from_secs(15 * 60),
+            long_break_every: 4,
+            max_pomodoros: 4,
+            auto_start_breaks: false,
+            auto_start_pomodoros: false,
+            auto_start_delay: Duration::from_secs(10),
+            on_suspend: SuspendPolicy::Count,
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use std::io::Write;
+
+use pomodoro::{Mode, Timer};
+use termion::{clear, cursor, terminal_size};
+
+use crate::config::Keys;
+use crate::todo::Picker;
+
+// 3x5 glyphs for the countdown, each pixel drawn two columns wide so the digits come out
+// roughly square.
+const GLYPH_HEIGHT: usize = 5;
+
+fn glyph(c: char) -> [&'static str; GLYPH_HEIGHT] {
+    match c {
+        '0' => ["###", "# #", "# #", "# #", "###"],
+        '1' => ["  #", "  #", "  #", "  #", "  #"],
+        '2' => ["###", "  #", "###", "#  ", "###"],
+        '3' => ["###", "  #", "###", "  #", "###"],
+        '4' => ["# #", "# #", "###", "  #", "  #"],
+        '5' => ["###", "#  ", "###", "  #", "###"],
+        '6' => ["###", "#  ", "###", "# #", "###"],
+        '7' => ["###", "  #", "  #", "  #", "  #"],
+        '8' => ["###", "# #", "###", "# #", "###"],
+        '9' => ["###", "# #", "###", "  #", "###"],
+        ':' => [" ", "#", " ", "#", " "],
+        _ => [" ", " ", " ", " ", " "],
+    }
+}
+
+// The rows of `text` in big digits.
+fn big(text: &str) -> Vec<String> {
+    (0..GLYPH_HEIGHT)
+        .map(|row| {
+            let glyphs: Vec<String> = text
+                .chars()
+                .map(|c| glyph(c)[row].replace('#', "██").replace(' ', "  "))
+                .collect();
+            glyphs.join("  ")
+        })
+        .collect()
+}
+
+fn progress_bar(timer: &Timer, width: usize) -> String {
+    let interval = timer.interval();
+    let done = if interval.duration().as_secs() == 0 {
+        1.0
+    } else {
+        (interval.elapsed().as_secs_f64() / interval.duration().as_secs_f64()).min(1.0)
+    };
+    let filled = (done * width as f64).round() as usize;
+    format!(
+        "{}{} {:>3}%",
+        "█".repeat(filled),
+        "░".repeat(width - filled),
+        (done * 100.0).round()
+    )
+}
+
+// One marker per pomodoro in the session: done, running and still to come.
+fn markers(timer: &Timer) -> String {
+    let state = timer.state();
+    // the count moves on to the next pomodoro as soon as the break starts
+    let done = match timer.mode() {
+        Mode::PomodoroEnded | Mode::End => state.pomodoro_count(),
+        _ => state.pomodoro_count() - 1,
+    };
+    (1..=state.max_pomodoros())
+        .map(|n| {
+            if n <= done {
+                "●"
+            } else if n == done + 1 && timer.mode() == Mode::Pomodoro {
+                "◉"
+            } else {
+                "○"
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn title(timer: &Timer) -> String {
+    let state = timer.state();
+    let title = match timer.mode() {
+        Mode::EnteringPomodoro | Mode::Pomodoro | Mode::PomodoroEnded | Mode::End => format!(
+            "Pomodoro {} of {}",
+            state.pomodoro_count(),
+            state.max_pomodoros()
+        ),
+        Mode::EnteringBreak | Mode::Break | Mode::BreakEnded => {
+            format!("Break {}", state.break_count())
+        }
+        Mode::EnteringLongBreak | Mode::LongBreak | Mode::LongBreakEnded => {
+            format!("Long break {}", state.break_count())
+        }
+    };
+    if timer.is_paused() {
+        format!("{} (paused)", title)
+    } else {
+        title
+    }
+}
+
+fn footer(keys: &Keys) -> String {
+    format!(
+        "{} pause  {} skip  {} restart  {} void  {}/{} more/less  {}/{} interrupt  {} task  {} quit",
+        keys.pause,
+        keys.skip,
+        keys.restart,
+        keys.void,
+        keys.extend,
+        keys.shorten,
+        keys.internal_interruption,
+        keys.external_interruption,
+        keys.task,
+        keys.quit
+    )
+}
+
+// Redraws the whole screen: the countdown in big digits with a progress bar, the session's
+// pomodoros and interruptions, the task, `message` (if any) and the keys at the bottom.
+pub fn draw(stdout: &mut impl Write, timer: &Timer, keys: &Keys, message: Option<&str>) {
+    let (width, height) = terminal_size().unwrap_or((80, 24));
+
+    let mut lines = vec![title(timer), String::new()];
+    lines.extend(big(&timer.interval().to_string()));
+    lines.push(String::new());
+    lines.push(progress_bar(
+        timer,
+        (width as usize).saturating_sub(10).min(50),
+    ));
+    lines.push(String::new());
+    lines.push(format!("{}{}", markers(timer), crate::tally(timer)));
+    lines.push(String::new());
+    lines.push(timer.state().task().unwrap_or_default().to_string());
+    lines.push(String::new());
+    lines.push(message.unwrap_or_default().to_string());
+
+    write!(stdout, "{}", clear::All).unwrap();
+    // leave the last row to the footer
+    let top = (height as usize).saturating_sub(lines.len() + 1) / 2 + 1;
+    for (i, line) in lines.iter().enumerate() {
+        write_centered(stdout, line, width, (top + i) as u16);
+    }
+    write_centered(stdout, &footer(keys), width, height);
+}
+
+// Lists the todo.txt items to pick the next pomodoro's task from, as many as fit around the
+// selected one, with the keys at the bottom.
+pub fn draw_picker(stdout: &mut impl Write, picker: &Picker, keys: &Keys) {
+    let (width, height) = terminal_size().unwrap_or((80, 24));
+    // the title, a blank line and the footer take three rows
+    let rows = (height as usize).saturating_sub(4).max(1);
+    let items = picker.items();
+    let first = picker
+        .selected()
+        .saturating_sub(rows / 2)
+        .min(items.len().saturating_sub(rows));
+
+    let lines: Vec<String> = items
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(rows)
+        .map(|(i, item)| {
+            let mark = if i == picker.selected() { ">" } else { " " };
+            format!("{} {}", mark, item.line)
+        })
+        .collect();
+
+    write!(stdout, "{}", clear::All).unwrap();
+    write_centered(stdout, "Task for the next pomodoro", width, 1);
+    // left-aligned, as a block in the middle
+    let longest = lines.iter().map(|line| line.chars().count()).max();
+    let column = (width as usize).saturating_sub(longest.unwrap_or(0)) / 2 + 1;
+    for (i, line) in lines.iter().enumerate() {
+        write!(
+            stdout,
+            "{}{}",
+            cursor::Goto(column as u16, (i + 3) as u16),
+            line
+        )
+        .unwrap();
+    }
+    let footer = format!(
+        "{}/{} move  enter pick  {} done  esc keep  {} quit",
+        keys.up, keys.down, keys.done, keys.quit
+    );
+    write_centered(stdout, &footer, width, height);
+}
+
+fn write_centered(stdout: &mut impl Write, line: &str, width: u16, row: u16) {
+    let len = line.chars().count();
+    let column = (width as usize).saturating_sub(len) / 2 + 1;
+    write!(stdout, "{}{}", cursor::Goto(column as u16, row), line).unwrap();
+}
@@ -0,0 +1,78 @@
+use std::borrow::Cow;
+use std::fs;
+use std::io;
+use std::io::Cursor;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::time::Duration;
+
+use rodio::{DeviceTrait, Source};
+
+// include_bytes! adds the song to the binary
+const GONG: &[u8] = include_bytes!("indian-gong.mp3");
+
+#[derive(Clone)]
+pub struct Sound {
+    bytes: Cow<'static, [u8]>,
+}
+
+impl Sound {
+    pub fn gong() -> Sound {
+        Sound {
+            bytes: Cow::Borrowed(GONG),
+        }
+    }
+
+    // Accepts anything `rodio::Decoder` can decode. The file is decoded once up front so that a
+    // bad file is reported at startup rather than when the pomodoro ends.
+    pub fn from_file(path: &Path) -> io::Result<Sound> {
+        let bytes = fs::read(path)?;
+        if let Err(e) = rodio::Decoder::new(Cursor::new(bytes.clone())) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, e.to_string()));
+        }
+
+        Ok(Sound {
+            bytes: Cow::Owned(bytes),
+        })
+    }
+
+    // TODO: add option to play synchronously when ending
+    // Fails instead of panicking when there is nothing to play on, e.g. in containers and SSH
+    // sessions. rodio itself panics if the device has no usable output format, so that's checked
+    // here before handing the sound over, and on anything else it can't cope with, like a device
+    // that's busy or can't be opened, so those panics are caught.
+    pub fn play(&self) -> io::Result<()> {
+        let device = match rodio::default_output_device() {
+            Some(device) => device,
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "no audio output device",
+                ))
+            }
+        };
+        if let Err(e) = device.default_output_format() {
+            return Err(io::Error::other(e.to_string()));
+        }
+
+        let cursor = Cursor::new(self.bytes.clone());
+        let source = rodio::Decoder::new(cursor)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        let source = source.take_duration(Duration::from_secs(20)); // there's something off about the duration
+                                                                    // the default hook would print over the screen
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(|_| {}));
+        let played = panic::catch_unwind(AssertUnwindSafe(|| {
+            rodio::play_raw(&device, source.convert_samples())
+        }));
+        panic::set_hook(hook);
+        played.map_err(|_| io::Error::other("the audio device failed"))
+    }
+}
+
+// One sound per transition that rings.
+pub struct Sounds {
+    pub pomodoro_end: Sound,
+    pub break_end: Sound,
+    pub done: Sound,
+}
@@ -0,0 +1,190 @@
+//! Per-day and per-week summaries of the history.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+
+use crate::history::{Entry, InterruptionKind, Kind, Outcome};
+
+#[derive(Default)]
+struct Totals {
+    completed_pomodoros: u32,
+    skipped_pomodoros: u32,
+    abandoned_pomodoros: u32,
+    focus_secs: u64,
+    break_secs: u64,
+    internal_interruptions: u32,
+    external_interruptions: u32,
+}
+
+impl Totals {
+    fn add(&mut self, entry: &Entry) {
+        match entry.kind {
+            Kind::Pomodoro => {
+                match entry.outcome {
+                    Outcome::Completed => self.completed_pomodoros += 1,
+                    // cut short, but the session moved on to its break
+                    Outcome::Skipped => self.skipped_pomodoros += 1,
+                    Outcome::Quit | Outcome::Voided => self.abandoned_pomodoros += 1,
+                    // the run that follows has an entry of its own, which counts instead
+                    Outcome::Restarted => (),
+                }
+                self.focus_secs += entry.elapsed_secs;
+                for interruption in &entry.interruptions {
+                    match interruption.kind {
+                        InterruptionKind::Internal => self.internal_interruptions += 1,
+                        InterruptionKind::External => self.external_interruptions += 1,
+                    }
+                }
+            }
+            Kind::Break | Kind::LongBreak => self.break_secs += entry.elapsed_secs,
+        }
+    }
+
+    fn into_row(self, period: String) -> Row {
+        Row {
+            period,
+            completed_pomodoros: self.completed_pomodoros,
+            skipped_pomodoros: self.skipped_pomodoros,
+            abandoned_pomodoros: self.abandoned_pomodoros,
+            focus_minutes: self.focus_secs / 60,
+            break_minutes: self.break_secs / 60,
+            internal_interruptions: self.internal_interruptions,
+            external_interruptions: self.external_interruptions,
+        }
+    }
+}
+
+/// The totals for one day or week.
+#[derive(Serialize)]
+pub struct Row {
+    period: String,
+    completed_pomodoros: u32,
+    skipped_pomodoros: u32,
+    abandoned_pomodoros: u32,
+    focus_minutes: u64,
+    break_minutes: u64,
+    internal_interruptions: u32,
+    external_interruptions: u32,
+}
+
+/// Totals per day and per week, oldest first. Displays as two tables.
+#[derive(Serialize)]
+pub struct Report {
+    days: Vec<Row>,
+    weeks: Vec<Row>,
+}
+
+/// Groups the history by the local day and ISO week an interval started in. `since` and
+/// `until` are inclusive.
+pub fn summarize(entries: &[Entry], since: Option<NaiveDate>, until: Option<NaiveDate>) -> Report {
+    let mut days: BTreeMap<NaiveDate, Totals> = BTreeMap::new();
+    let mut weeks: BTreeMap<(i32, u32), Totals> = BTreeMap::new();
+
+    for entry in entries {
+        let day = entry.started_at.date_naive();
+        if since.is_some_and(|since| day < since) || until.is_some_and(|until| day > until) {
+            continue;
+        }
+
+        let week = day.iso_week();
+        days.entry(day).or_default().add(entry);
+        weeks
+            .entry((week.year(), week.week()))
+            .or_default()
+            .add(entry);
+    }
+
+    Report {
+        days: days
+            .into_iter()
+            .map(|(day, totals)| totals.into_row(day.format("%Y-%m-%d").to_string()))
+            .collect(),
+        weeks: weeks
+            .into_iter()
+            .map(|((year, week), totals)| totals.into_row(format!("{}-W{:02}", year, week)))
+            .collect(),
+    }
+}
+
+fn write_rows(f: &mut Formatter<'_>, heading: &str, rows: &[Row]) -> fmt::Result {
+    writeln!(
+        f,
+        "{:<10}  {:>9}  {:>7}  {:>9}  {:>5}  {:>5}  {:>8}  {:>8}",
+        heading, "Pomodoros", "Skipped", "Abandoned", "Focus", "Break", "Internal", "External"
+    )?;
+    for row in rows {
+        writeln!(
+            f,
+            "{:<10}  {:>9}  {:>7}  {:>9}  {:>4}m  {:>4}m  {:>8}  {:>8}",
+            row.period,
+            row.completed_pomodoros,
+            row.skipped_pomodoros,
+            row.abandoned_pomodoros,
+            row.focus_minutes,
+            row.break_minutes,
+            row.internal_interruptions,
+            row.external_interruptions,
+        )?;
+    }
+    Ok(())
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.days.is_empty() {
+            return writeln!(f, "No sessions recorded.");
+        }
+
+        write_rows(f, "Day", &self.days)?;
+        writeln!(f)?;
+        write_rows(f, "Week", &self.weeks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, TimeZone};
+
+    fn pomodoro(outcome: Outcome, elapsed_secs: u64) -> Entry {
+        Entry {
+            kind: Kind::Pomodoro,
+            task: None,
+            started_at: Local.with_ymd_and_hms(2026, 10, 18, 9, 0, 0).unwrap(),
+            planned_secs: 25 * 60,
+            adjusted_secs: 0,
+            elapsed_secs,
+            paused_secs: 0,
+            suspended_secs: 0,
+            on_suspend: None,
+            reason: None,
+            interruptions: Vec::new(),
+            outcome,
+        }
+    }
+
+    #[test]
+    fn counts_pomodoros_by_outcome() {
+        let entries = [
+            // restarted once, then finished: one pomodoro
+            pomodoro(Outcome::Restarted, 5 * 60),
+            pomodoro(Outcome::Completed, 25 * 60),
+            pomodoro(Outcome::Skipped, 20 * 60),
+            pomodoro(Outcome::Voided, 10 * 60),
+            pomodoro(Outcome::Quit, 10 * 60),
+        ];
+        let report = summarize(&entries, None, None);
+
+        let day = &report.days[0];
+        assert_eq!(day.completed_pomodoros, 1);
+        assert_eq!(day.skipped_pomodoros, 1);
+        assert_eq!(day.abandoned_pomodoros, 2);
+        // all of it was spent focusing
+        assert_eq!(day.focus_minutes, 70);
+        assert_eq!(report.weeks.len(), 1);
+    }
+}
@@ -0,0 +1,179 @@
+//! The task list, with estimated and actual pomodoros per task.
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::data;
+
+/// Something to spend pomodoros on.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Task {
+    /// Identifies the task on the command line. Never reused.
+    pub id: u32,
+    /// What the task is, used as the timer's task.
+    pub name: String,
+    /// How many pomodoros it was expected to take.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<u32>,
+    /// How many pomodoros were completed on it.
+    #[serde(default)]
+    pub actual: u32,
+    /// Whether it's finished.
+    #[serde(default)]
+    pub done: bool,
+}
+
+/// All tasks, in the order they were added, and the one being worked on.
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct TaskList {
+    tasks: Vec<Task>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    active: Option<u32>,
+}
+
+impl TaskList {
+    /// Every task, finished or not.
+    pub fn tasks(&self) -> &[Task] {
+        &self.tasks
+    }
+
+    /// The task being worked on: the one picked with [`start`](TaskList::start) while it's
+    /// unfinished, otherwise the oldest unfinished one.
+    pub fn active(&self) -> Option<&Task> {
+        let picked = self
+            .active
+            .and_then(|id| self.tasks.iter().find(|task| task.id == id && !task.done));
+        picked.or_else(|| self.tasks.iter().find(|task| !task.done))
+    }
+
+    /// Adds a task, returning its id.
+    pub fn add(&mut self, name: String, estimate: Option<u32>) -> u32 {
+        let id = self.tasks.iter().map(|task| task.id).max().unwrap_or(0) + 1;
+        self.tasks.push(Task {
+            id,
+            name,
+            estimate,
+            actual: 0,
+            done: false,
+        });
+        id
+    }
+
+    /// Picks the task to work on. Returns whether there's an unfinished task with that id.
+    pub fn start(&mut self, id: u32) -> bool {
+        if !self.tasks.iter().any(|task| task.id == id && !task.done) {
+            return false;
+        }
+        self.active = Some(id);
+        true
+    }
+
+    /// Marks a task finished. Returns whether there's a task with that id.
+    pub fn finish(&mut self, id: u32) -> bool {
+        match self.tasks.iter_mut().find(|task| task.id == id) {
+            Some(task) => {
+                task.done = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Counts a completed pomodoro towards the unfinished task with that id. Returns whether
+    /// there is one.
+    pub fn count_pomodoro(&mut self, id: u32) -> bool {
+        match self
+            .tasks
+            .iter_mut()
+            .find(|task| task.id == id && !task.done)
+        {
+            Some(task) => {
+                task.actual += 1;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// The task list lives next to the history, e.g. ~/.local/share/pomodoro/tasks.json
+pub fn path() -> io::Result<PathBuf> {
+    data::file("tasks.json")
+}
+
+/// Reads the task list. A missing file is an empty list.
+pub fn load() -> io::Result<TaskList> {
+    let contents = match fs::read_to_string(path()?) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(TaskList::default()),
+        Err(e) => return Err(e),
+    };
+    serde_json::from_str(&contents).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Replaces the saved task list, so that it's never half written.
+pub fn save(tasks: &TaskList) -> io::Result<()> {
+    data::write_atomically(&path()?, serde_json::to_string_pretty(tasks)?.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_tasks_past_the_highest_id() {
+        let mut list = TaskList::default();
+        assert_eq!(list.add("Write RFC".to_string(), Some(3)), 1);
+        assert_eq!(list.add("Review PR".to_string(), None), 2);
+        // finished tasks keep their ids
+        list.finish(2);
+        assert_eq!(list.add("Call mom".to_string(), None), 3);
+
+        // nor are the ids of tasks taken out of the file by hand given out again
+        let mut list: TaskList =
+            serde_json::from_str(r#"{"tasks":[{"id":1,"name":"a"},{"id":5,"name":"b"}]}"#).unwrap();
+        assert_eq!(list.add("c".to_string(), None), 6);
+    }
+
+    #[test]
+    fn works_on_the_picked_or_oldest_unfinished_task() {
+        let mut list = TaskList::default();
+        assert!(list.active().is_none());
+        list.add("Write RFC".to_string(), None);
+        list.add("Review PR".to_string(), None);
+        list.add("Call mom".to_string(), None);
+        assert_eq!(list.active().unwrap().id, 1);
+
+        assert!(list.start(3));
+        assert_eq!(list.active().unwrap().id, 3);
+        // back to the oldest unfinished one once the picked one is done
+        list.finish(3);
+        list.finish(1);
+        assert_eq!(list.active().unwrap().id, 2);
+
+        assert!(!list.start(1));
+        assert!(!list.start(4));
+        assert!(!list.finish(4));
+        assert_eq!(list.active().unwrap().id, 2);
+    }
+
+    #[test]
+    fn counts_pomodoros_towards_unfinished_tasks() {
+        let mut list = TaskList::default();
+        list.add("Write RFC".to_string(), None);
+        list.add("Write RFC".to_string(), None);
+        assert!(list.count_pomodoro(2));
+        assert!(list.count_pomodoro(2));
+        // not towards another task of the same name
+        assert_eq!(list.tasks()[0].actual, 0);
+        assert_eq!(list.tasks()[1].actual, 2);
+
+        list.finish(1);
+        assert!(!list.count_pomodoro(1));
+        assert!(!list.count_pomodoro(3));
+        assert_eq!(list.tasks()[0].actual, 0);
+    }
+}
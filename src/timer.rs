@@ -0,0 +1,719 @@
+//! The state machine and its intervals, put together.
+
+use std::time::Duration;
+
+use crate::checkpoint::Checkpoint;
+use crate::clock::{Clock, SystemClock};
+use crate::engine::{Mode, StateMachine};
+use crate::event::Event;
+use crate::history;
+use crate::interval::Interval;
+use crate::schedule::{Schedule, SuspendPolicy};
+use crate::tasks::Task;
+
+// Suspends shorter than this are let go: the interval just doesn't count them.
+const SUSPEND_THRESHOLD: Duration = Duration::from_secs(30);
+
+/// Runs a session laid out by a [`Schedule`], keeping time with a [`Clock`]. Drive it with
+/// [`tick`](Timer::tick) and the user's actions; each of them returns the [`Event`]s it caused.
+#[derive(Debug)]
+pub struct Timer<C: Clock = SystemClock> {
+    schedule: Schedule,
+    state: StateMachine,
+    interval: Interval,
+    paused: bool,
+    acked: bool,
+    // time spent waiting for an acknowledgement, and whether the user asked to hold off
+    // auto-starting
+    waited: Duration,
+    held: bool,
+    clock: C,
+    // the clock's time and time spent suspended at the previous tick
+    last_tick: Duration,
+    last_suspended: Duration,
+}
+
+impl Timer {
+    /// A session running on the system clock, about to start its first pomodoro. That happens
+    /// on the first `tick`.
+    pub fn new(schedule: Schedule, task: Option<String>) -> Timer {
+        Timer::with_clock(schedule, task, SystemClock::new())
+    }
+}
+
+impl<C: Clock> Timer<C> {
+    /// A session running on `clock`, about to start its first pomodoro. That happens on the
+    /// first `tick`.
+    pub fn with_clock(schedule: Schedule, task: Option<String>, clock: C) -> Timer<C> {
+        let state = StateMachine::new(schedule.max_pomodoros, schedule.long_break_every, task);
+        let interval = Interval::new(schedule.pomodoro, &clock);
+        Timer::from_parts(schedule, state, interval, false, clock)
+    }
+
+    /// Picks a session up where `checkpoint` left it, running on `clock`. The session keeps the
+    /// counts and length it had; `schedule` lays out the intervals still to come. Time that
+    /// passed since the checkpoint was taken doesn't count.
+    pub fn restore(schedule: Schedule, checkpoint: Checkpoint, clock: C) -> Timer<C> {
+        Timer::from_parts(
+            schedule,
+            checkpoint.state,
+            checkpoint.interval,
+            checkpoint.paused,
+            clock,
+        )
+    }
+
+    fn from_parts(
+        schedule: Schedule,
+        state: StateMachine,
+        interval: Interval,
+        paused: bool,
+        clock: C,
+    ) -> Timer<C> {
+        Timer {
+            schedule,
+            state,
+            interval,
+            paused,
+            acked: false,
+            waited: Duration::from_secs(0),
+            held: false,
+            last_tick: clock.elapsed(),
+            last_suspended: clock.suspended(),
+            clock,
+        }
+    }
+
+    /// A snapshot of the session to save, to [`restore`](Timer::restore) it after a crash.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            state: self.state.clone(),
+            interval: self.interval.clone(),
+            paused: self.paused,
+            saved_at: self.clock.wall(),
+        }
+    }
+
+    /// The clock the session keeps time with.
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// The session's layout.
+    pub fn schedule(&self) -> &Schedule {
+        &self.schedule
+    }
+
+    /// Counts, mode and task.
+    pub fn state(&self) -> &StateMachine {
+        &self.state
+    }
+
+    /// The current mode.
+    pub fn mode(&self) -> Mode {
+        self.state.mode()
+    }
+
+    /// The current (or last) interval.
+    pub fn interval(&self) -> &Interval {
+        &self.interval
+    }
+
+    /// Whether the running interval is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Changes what the current (or next) pomodoro is spent on.
+    pub fn set_task(&mut self, task: Option<String>) {
+        self.state.set_task(task);
+    }
+
+    /// Spends the current (or next) pomodoro on an entry of the task list, so that it can be
+    /// counted towards that entry.
+    pub fn set_listed_task(&mut self, task: &Task) {
+        self.state.set_listed_task(task);
+    }
+
+    /// Pauses or resumes the running interval. Does nothing between intervals.
+    pub fn toggle_pause(&mut self) -> Option<Event> {
+        if !self.mode().is_running() {
+            return None;
+        }
+
+        self.paused = !self.paused;
+        if self.paused {
+            Some(Event::Paused)
+        } else {
+            Some(Event::Resumed)
+        }
+    }
+
+    /// Ends the running interval early and moves on to the next one without waiting for an
+    /// acknowledgement.
+    pub fn skip(&mut self) -> Vec<Event> {
+        if !self.mode().is_running() {
+            return Vec::new();
+        }
+
+        let events = self.end_interval(history::Outcome::Skipped);
+        self.acknowledge();
+        events
+    }
+
+    /// Starts the running interval over from zero.
+    pub fn restart(&mut self) -> Option<Event> {
+        let entry = self.record(history::Outcome::Restarted)?;
+        self.paused = false;
+        self.interval.restart(&self.clock);
+        Some(Event::Restarted(entry))
+    }
+
+    /// Throws the running pomodoro away, e.g. because an interruption took over, and starts it
+    /// over. It's recorded as voided, with the `reason` if given, and doesn't count.
+    pub fn void(&mut self, reason: Option<String>) -> Option<Event> {
+        if self.mode() != Mode::Pomodoro {
+            return None;
+        }
+
+        let mut entry = self.record(history::Outcome::Voided)?;
+        entry.reason = reason;
+        self.paused = false;
+        self.interval.restart(&self.clock);
+        Some(Event::Restarted(entry))
+    }
+
+    /// Logs an interruption of the running pomodoro, which keeps running. Breaks can't be
+    /// interrupted. Returns whether it was logged.
+    pub fn interrupt(&mut self, kind: history::InterruptionKind) -> bool {
+        if self.mode() != Mode::Pomodoro {
+            return false;
+        }
+        self.interval.interrupt(kind);
+        true
+    }
+
+    /// Says what the latest interruption of the running pomodoro was about. Returns whether
+    /// there's one to note, which isn't the case anymore once the pomodoro ended or started
+    /// over.
+    pub fn note_interruption(&mut self, note: String) -> bool {
+        self.mode() == Mode::Pomodoro && self.interval.note_interruption(note)
+    }
+
+    /// Adds time to the running interval.
+    pub fn extend(&mut self, by: Duration) {
+        if self.mode().is_running() {
+            self.interval.extend(by);
+        }
+    }
+
+    /// Takes time off the running interval. It ends on the next `tick` if that's more than
+    /// what's left.
+    pub fn shorten(&mut self, by: Duration) {
+        if self.mode().is_running() {
+            self.interval.shorten(by);
+        }
+    }
+
+    /// Lets the session move on after an interval ended, whether or not it was going to start on
+    /// its own. Takes effect on the next `tick`.
+    pub fn acknowledge(&mut self) {
+        if self.mode().is_waiting() {
+            self.acked = true;
+        }
+    }
+
+    /// How long until the next interval starts on its own, if it's going to.
+    pub fn auto_start_in(&self) -> Option<Duration> {
+        let auto_start = match self.mode() {
+            Mode::PomodoroEnded => self.schedule.auto_start_breaks,
+            Mode::BreakEnded | Mode::LongBreakEnded => self.schedule.auto_start_pomodoros,
+            _ => false,
+        };
+        if !auto_start || self.held {
+            return None;
+        }
+
+        Some(
+            self.schedule
+                .auto_start_delay
+                .checked_sub(self.waited)
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Keeps the next interval from starting on its own. It waits for an acknowledgement
+    /// instead.
+    pub fn hold(&mut self) {
+        if self.mode().is_waiting() {
+            self.held = true;
+        }
+    }
+
+    /// Lets the next interval start on its own again after a [`hold`](Timer::hold), if it was
+    /// going to. Time already waited counts towards the delay.
+    pub fn release(&mut self) {
+        self.held = false;
+    }
+
+    /// Cuts the session short, recording the running interval if there is one.
+    pub fn quit(&mut self) -> Event {
+        Event::Quit(self.record(history::Outcome::Quit))
+    }
+
+    /// Counts the time passed since the previous tick against the running interval and moves
+    /// the session along as far as it can go without the user.
+    pub fn tick(&mut self) -> Vec<Event> {
+        let now = self.clock.elapsed();
+        let elapsed = now - self.last_tick;
+        self.last_tick = now;
+        let suspended = self.clock.suspended();
+        let gap = suspended
+            .checked_sub(self.last_suspended)
+            .filter(|gap| *gap >= SUSPEND_THRESHOLD);
+        self.last_suspended = suspended;
+
+        let mut events = Vec::new();
+        if let Some(gap) = gap {
+            events.extend(self.suspended(gap));
+        }
+
+        if self.mode().is_running() {
+            if self.paused {
+                self.interval.add_paused(elapsed);
+            } else {
+                self.interval -= elapsed;
+            }
+        } else if self.mode().is_waiting() {
+            self.waited += elapsed;
+            if self.auto_start_in() == Some(Duration::from_secs(0)) {
+                self.acked = true;
+            }
+        }
+
+        // The nice thing about using match with Enums in Rust is you get
+        // exhaustive match checking. This ensures you're covering all cases.
+        loop {
+            match self.mode() {
+                Mode::EnteringPomodoro => {
+                    self.interval = Interval::new(self.schedule.pomodoro, &self.clock);
+                    self.state.next_state();
+                    events.push(Event::PomodoroStarted);
+                }
+                Mode::Pomodoro if self.interval.has_ended() => {
+                    events.extend(self.end_interval(history::Outcome::Completed));
+                }
+                Mode::EnteringBreak => {
+                    self.interval = Interval::new(self.schedule.short_break, &self.clock);
+                    self.state.next_state();
+                    events.push(Event::BreakStarted { long: false });
+                }
+                Mode::EnteringLongBreak => {
+                    self.interval = Interval::new(self.schedule.long_break, &self.clock);
+                    self.state.next_state();
+                    events.push(Event::BreakStarted { long: true });
+                }
+                Mode::Break | Mode::LongBreak if self.interval.has_ended() => {
+                    events.extend(self.end_interval(history::Outcome::Completed));
+                }
+                Mode::PomodoroEnded | Mode::BreakEnded | Mode::LongBreakEnded if self.acked => {
+                    self.acked = false;
+                    self.state.next_state();
+                }
+                _ => break,
+            }
+        }
+        events
+    }
+
+    // Deals with the system having been suspended for `gap`.
+    fn suspended(&mut self, gap: Duration) -> Vec<Event> {
+        let mut events = vec![Event::Suspended(gap)];
+        if self.mode().is_waiting() {
+            self.waited += gap;
+        }
+        if !self.mode().is_running() {
+            return events;
+        }
+
+        let policy = match (self.schedule.on_suspend, self.mode()) {
+            _ if self.paused => SuspendPolicy::Pause,
+            (SuspendPolicy::Void, Mode::Break) | (SuspendPolicy::Void, Mode::LongBreak) => {
+                SuspendPolicy::Count
+            }
+            (policy, _) => policy,
+        };
+        self.interval.add_suspended(gap, policy);
+        match policy {
+            SuspendPolicy::Count => (),
+            SuspendPolicy::Pause => {
+                if !self.paused {
+                    self.paused = true;
+                    events.push(Event::Paused);
+                }
+            }
+            SuspendPolicy::Void => {
+                let entry = self.record(history::Outcome::Voided).unwrap();
+                self.interval.restart(&self.clock);
+                events.push(Event::Restarted(entry));
+            }
+        }
+        events
+    }
+
+    // Records the running interval and moves on to waiting for the next one.
+    fn end_interval(&mut self, outcome: history::Outcome) -> Vec<Event> {
+        let entry = self.record(outcome).unwrap();
+        let pomodoro = entry.kind == history::Kind::Pomodoro;
+        self.paused = false;
+        self.state.next_state();
+        self.waited = Duration::from_secs(0);
+        self.held = false;
+
+        if !pomodoro {
+            return vec![Event::BreakEnded(entry)];
+        }
+        let mut events = vec![Event::PomodoroEnded(entry)];
+        if self.mode() == Mode::End {
+            events.push(Event::Done);
+        }
+        events
+    }
+
+    // The history record of the running interval, if any. Only pomodoros are spent on a task.
+    fn record(&self, outcome: history::Outcome) -> Option<history::Entry> {
+        let kind = self.mode().history_kind()?;
+        let task = if kind == history::Kind::Pomodoro {
+            self.state.task()
+        } else {
+            None
+        };
+        Some(self.interval.to_history(kind, task, outcome))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::VirtualClock;
+    use crate::history::Outcome;
+
+    fn minutes(n: u64) -> Duration {
+        Duration::from_secs(n * 60)
+    }
+
+    // A started session on a clock the test moves along.
+    fn start(schedule: Schedule) -> (Timer<VirtualClock>, VirtualClock) {
+        let clock = VirtualClock::new();
+        let mut timer = Timer::with_clock(schedule, Some("Write RFC".to_string()), clock.clone());
+        let events = timer.tick();
+        assert!(matches!(events[..], [Event::PomodoroStarted]));
+        (timer, clock)
+    }
+
+    // Lets `by` pass, then ticks.
+    fn after(timer: &mut Timer<VirtualClock>, clock: &VirtualClock, by: Duration) -> Vec<Event> {
+        clock.advance(by);
+        timer.tick()
+    }
+
+    // Acknowledges the interval that ended and ticks into the next one.
+    fn go_on(timer: &mut Timer<VirtualClock>) -> Vec<Event> {
+        timer.acknowledge();
+        timer.tick()
+    }
+
+    #[test]
+    fn runs_a_session_with_long_breaks() {
+        let schedule = Schedule {
+            long_break_every: 2,
+            max_pomodoros: 4,
+            ..Schedule::default()
+        };
+        let (mut timer, clock) = start(schedule);
+
+        for n in 1..=3 {
+            assert_eq!(timer.mode(), Mode::Pomodoro);
+            assert_eq!(timer.state().pomodoro_count(), n);
+            let events = after(&mut timer, &clock, minutes(25));
+            match &events[..] {
+                [Event::PomodoroEnded(entry)] => {
+                    assert_eq!(entry.outcome, Outcome::Completed);
+                    assert_eq!(entry.elapsed_secs, 25 * 60);
+                    assert_eq!(entry.task.as_deref(), Some("Write RFC"));
+                }
+                events => panic!("unexpected events {:?}", events),
+            }
+            assert_eq!(timer.mode(), Mode::PomodoroEnded);
+            // nothing starts without an acknowledgement
+            assert!(after(&mut timer, &clock, minutes(60)).is_empty());
+
+            let long = n % 2 == 0;
+            let events = go_on(&mut timer);
+            assert!(matches!(events[..], [Event::BreakStarted { long: l }] if l == long));
+            let length = if long { minutes(15) } else { minutes(4) };
+            assert_eq!(timer.interval().duration(), length);
+            let events = after(&mut timer, &clock, length);
+            match &events[..] {
+                [Event::BreakEnded(entry)] => {
+                    assert_eq!(entry.outcome, Outcome::Completed);
+                    assert_eq!(entry.task, None);
+                }
+                events => panic!("unexpected events {:?}", events),
+            }
+            assert!(matches!(go_on(&mut timer)[..], [Event::PomodoroStarted]));
+        }
+
+        let events = after(&mut timer, &clock, minutes(25));
+        assert!(matches!(events[..], [Event::PomodoroEnded(_), Event::Done]));
+        assert_eq!(timer.mode(), Mode::End);
+        assert!(go_on(&mut timer).is_empty());
+    }
+
+    #[test]
+    fn doesnt_count_paused_time() {
+        let (mut timer, clock) = start(Schedule::default());
+        after(&mut timer, &clock, minutes(10));
+
+        assert!(matches!(timer.toggle_pause(), Some(Event::Paused)));
+        assert!(after(&mut timer, &clock, minutes(5)).is_empty());
+        assert_eq!(timer.interval().remaining(), minutes(15));
+        assert_eq!(timer.interval().paused(), minutes(5));
+
+        assert!(matches!(timer.toggle_pause(), Some(Event::Resumed)));
+        match &after(&mut timer, &clock, minutes(15))[..] {
+            [Event::PomodoroEnded(entry)] => {
+                assert_eq!(entry.elapsed_secs, 25 * 60);
+                assert_eq!(entry.paused_secs, 5 * 60);
+            }
+            events => panic!("unexpected events {:?}", events),
+        }
+        // nothing to pause between intervals
+        assert!(timer.toggle_pause().is_none());
+    }
+
+    #[test]
+    fn auto_starts_after_the_delay_unless_held() {
+        let schedule = Schedule {
+            auto_start_breaks: true,
+            auto_start_delay: Duration::from_secs(10),
+            ..Schedule::default()
+        };
+        let (mut timer, clock) = start(schedule);
+
+        after(&mut timer, &clock, minutes(25));
+        assert_eq!(timer.auto_start_in(), Some(Duration::from_secs(10)));
+        assert!(after(&mut timer, &clock, Duration::from_secs(4)).is_empty());
+        assert_eq!(timer.auto_start_in(), Some(Duration::from_secs(6)));
+        let events = after(&mut timer, &clock, Duration::from_secs(6));
+        assert!(matches!(events[..], [Event::BreakStarted { long: false }]));
+
+        // pomodoros don't start on their own
+        after(&mut timer, &clock, minutes(4));
+        assert_eq!(timer.auto_start_in(), None);
+        go_on(&mut timer);
+
+        after(&mut timer, &clock, minutes(25));
+        timer.hold();
+        assert_eq!(timer.auto_start_in(), None);
+        assert!(after(&mut timer, &clock, minutes(1)).is_empty());
+        assert_eq!(timer.mode(), Mode::PomodoroEnded);
+        assert!(matches!(
+            go_on(&mut timer)[..],
+            [Event::BreakStarted { .. }]
+        ));
+
+        // released, it starts right away since the delay has passed
+        after(&mut timer, &clock, minutes(4));
+        go_on(&mut timer);
+        after(&mut timer, &clock, minutes(25));
+        timer.hold();
+        assert!(after(&mut timer, &clock, minutes(1)).is_empty());
+        timer.release();
+        assert!(matches!(timer.tick()[..], [Event::BreakStarted { .. }]));
+    }
+
+    #[test]
+    fn skips_to_the_next_interval() {
+        let (mut timer, clock) = start(Schedule::default());
+        after(&mut timer, &clock, minutes(5));
+
+        match &timer.skip()[..] {
+            [Event::PomodoroEnded(entry)] => {
+                assert_eq!(entry.outcome, Outcome::Skipped);
+                assert_eq!(entry.elapsed_secs, 5 * 60);
+            }
+            events => panic!("unexpected events {:?}", events),
+        }
+        // without waiting for an acknowledgement
+        assert!(matches!(timer.tick()[..], [Event::BreakStarted { .. }]));
+
+        match &timer.skip()[..] {
+            [Event::BreakEnded(entry)] => assert_eq!(entry.outcome, Outcome::Skipped),
+            events => panic!("unexpected events {:?}", events),
+        }
+        assert!(matches!(timer.tick()[..], [Event::PomodoroStarted]));
+        assert_eq!(timer.state().pomodoro_count(), 2);
+    }
+
+    #[test]
+    fn restarts_the_running_interval() {
+        let (mut timer, clock) = start(Schedule::default());
+        after(&mut timer, &clock, minutes(10));
+        timer.extend(minutes(5));
+
+        match timer.restart() {
+            Some(Event::Restarted(entry)) => {
+                assert_eq!(entry.outcome, Outcome::Restarted);
+                assert_eq!(entry.elapsed_secs, 10 * 60);
+                assert_eq!(entry.adjusted_secs, 5 * 60);
+            }
+            event => panic!("unexpected event {:?}", event),
+        }
+        assert_eq!(timer.mode(), Mode::Pomodoro);
+        assert_eq!(timer.state().pomodoro_count(), 1);
+        // the extended length is kept
+        assert_eq!(timer.interval().remaining(), minutes(30));
+    }
+
+    #[test]
+    fn voids_the_running_pomodoro() {
+        let (mut timer, clock) = start(Schedule::default());
+        after(&mut timer, &clock, minutes(10));
+        timer.interrupt(history::InterruptionKind::External);
+
+        match timer.void(Some("fire alarm".to_string())) {
+            Some(Event::Restarted(entry)) => {
+                assert_eq!(entry.outcome, Outcome::Voided);
+                assert_eq!(entry.reason.as_deref(), Some("fire alarm"));
+                assert_eq!(entry.interruptions.len(), 1);
+            }
+            event => panic!("unexpected event {:?}", event),
+        }
+        assert_eq!(timer.state().pomodoro_count(), 1);
+        assert_eq!(timer.interval().remaining(), minutes(25));
+        assert!(timer.interval().interruptions().is_empty());
+
+        // breaks can't be voided
+        after(&mut timer, &clock, minutes(25));
+        go_on(&mut timer);
+        assert!(timer.void(None).is_none());
+    }
+
+    #[test]
+    fn notes_interruptions_of_the_running_pomodoro_only() {
+        let (mut timer, clock) = start(Schedule::default());
+        assert!(!timer.note_interruption("nothing yet".to_string()));
+
+        assert!(timer.interrupt(history::InterruptionKind::Internal));
+        assert!(timer.note_interruption("email".to_string()));
+        assert_eq!(
+            timer.interval().interruptions()[0].note.as_deref(),
+            Some("email")
+        );
+
+        timer.interrupt(history::InterruptionKind::External);
+        after(&mut timer, &clock, minutes(25));
+        assert!(!timer.note_interruption("too late".to_string()));
+        assert!(!timer.interrupt(history::InterruptionKind::External));
+    }
+
+    fn suspended_pomodoro(on_suspend: SuspendPolicy) -> (Timer<VirtualClock>, Vec<Event>) {
+        let schedule = Schedule {
+            on_suspend,
+            ..Schedule::default()
+        };
+        let (mut timer, clock) = start(schedule);
+        after(&mut timer, &clock, minutes(10));
+        clock.suspend(minutes(5));
+        let events = timer.tick();
+        (timer, events)
+    }
+
+    #[test]
+    fn counts_suspended_time() {
+        let (timer, events) = suspended_pomodoro(SuspendPolicy::Count);
+        assert!(matches!(events[..], [Event::Suspended(gap)] if gap == minutes(5)));
+        assert_eq!(timer.interval().elapsed(), minutes(15));
+        assert!(!timer.is_paused());
+    }
+
+    #[test]
+    fn pauses_on_suspend() {
+        let (timer, events) = suspended_pomodoro(SuspendPolicy::Pause);
+        assert!(matches!(events[..], [Event::Suspended(_), Event::Paused]));
+        assert_eq!(timer.interval().elapsed(), minutes(10));
+        assert_eq!(timer.interval().paused(), minutes(5));
+        assert!(timer.is_paused());
+    }
+
+    #[test]
+    fn voids_on_suspend() {
+        let (timer, events) = suspended_pomodoro(SuspendPolicy::Void);
+        match &events[..] {
+            [Event::Suspended(_), Event::Restarted(entry)] => {
+                assert_eq!(entry.outcome, Outcome::Voided);
+                assert_eq!(entry.suspended_secs, 5 * 60);
+                assert_eq!(entry.on_suspend, Some(SuspendPolicy::Void));
+            }
+            events => panic!("unexpected events {:?}", events),
+        }
+        assert_eq!(timer.interval().remaining(), minutes(25));
+        assert_eq!(timer.state().pomodoro_count(), 1);
+    }
+
+    #[test]
+    fn breaks_count_suspended_time_even_when_voiding() {
+        let schedule = Schedule {
+            on_suspend: SuspendPolicy::Void,
+            ..Schedule::default()
+        };
+        let (mut timer, clock) = start(schedule);
+        after(&mut timer, &clock, minutes(25));
+        go_on(&mut timer);
+
+        clock.suspend(minutes(10));
+        let events = timer.tick();
+        assert!(matches!(
+            events[..],
+            [Event::Suspended(_), Event::BreakEnded(_)]
+        ));
+    }
+
+    #[test]
+    fn ignores_short_gaps() {
+        let (mut timer, clock) = start(Schedule::default());
+        clock.suspend(Duration::from_secs(10));
+        assert!(after(&mut timer, &clock, minutes(1)).is_empty());
+        assert_eq!(timer.interval().elapsed(), minutes(1));
+    }
+
+    #[test]
+    fn restores_a_checkpoint() {
+        let (mut timer, clock) = start(Schedule::default());
+        after(&mut timer, &clock, minutes(25));
+        go_on(&mut timer);
+        after(&mut timer, &clock, minutes(4));
+        go_on(&mut timer);
+        after(&mut timer, &clock, minutes(10));
+        timer.toggle_pause();
+
+        let saved = serde_json::to_string(&timer.checkpoint()).unwrap();
+        let checkpoint = serde_json::from_str(&saved).unwrap();
+        let clock = VirtualClock::new();
+        let mut timer = Timer::restore(Schedule::default(), checkpoint, clock.clone());
+
+        assert_eq!(timer.mode(), Mode::Pomodoro);
+        assert_eq!(timer.state().pomodoro_count(), 2);
+        assert_eq!(timer.state().break_count(), 2);
+        assert_eq!(timer.state().task(), Some("Write RFC"));
+        assert_eq!(timer.interval().remaining(), minutes(15));
+        assert!(timer.is_paused());
+
+        // and goes on from there
+        assert!(after(&mut timer, &clock, minutes(5)).is_empty());
+        assert!(matches!(timer.toggle_pause(), Some(Event::Resumed)));
+        let events = after(&mut timer, &clock, minutes(15));
+        assert!(matches!(events[..], [Event::PomodoroEnded(_)]));
+    }
+}
@@ -0,0 +1,255 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use chrono::{Local, NaiveDate};
+use pomodoro::data;
+
+// An unfinished line of a todo.txt file, see https://github.com/todotxt/todo.txt
+pub struct Item {
+    // the line as it's written, for the picker
+    pub line: String,
+    pub priority: Option<char>,
+    // what the timer works on: the description without the priority, the creation date and the
+    // pomo:N tag, so the tag can change without losing track of the item
+    pub task: String,
+}
+
+impl Item {
+    fn parse(line: &str) -> Option<Item> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("x ") {
+            return None;
+        }
+
+        let (priority, rest) = split_priority(line);
+        let mut words = rest.split_whitespace().peekable();
+        if words.peek().is_some_and(|word| is_date(word)) {
+            words.next();
+        }
+        let task = words
+            .filter(|word| pomos(word).is_none())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        Some(Item {
+            line: line.to_string(),
+            priority,
+            task,
+        })
+    }
+}
+
+// "(A) rest" into `A` and "rest".
+fn split_priority(line: &str) -> (Option<char>, &str) {
+    let bytes = line.as_bytes();
+    if bytes.len() >= 4
+        && bytes[0] == b'('
+        && bytes[1].is_ascii_uppercase()
+        && bytes[2] == b')'
+        && bytes[3] == b' '
+    {
+        (Some(bytes[1] as char), &line[4..])
+    } else {
+        (None, line)
+    }
+}
+
+fn is_date(word: &str) -> bool {
+    NaiveDate::parse_from_str(word, "%Y-%m-%d").is_ok()
+}
+
+// The N of a pomo:N tag.
+fn pomos(word: &str) -> Option<u32> {
+    word.strip_prefix("pomo:")?.parse().ok()
+}
+
+// The unfinished items of the file, highest priority first and otherwise in the file's order.
+pub fn read(path: &Path) -> io::Result<Vec<Item>> {
+    let mut items: Vec<Item> = fs::read_to_string(path)?
+        .lines()
+        .filter_map(Item::parse)
+        .collect();
+    // items without a priority go last
+    items.sort_by_key(|item| item.priority.unwrap_or('['));
+    Ok(items)
+}
+
+// Rewrites the first unfinished line of the file working on `task` with `change`. Returns whether
+// there is one. The file is replaced atomically, following a symlink to it, and keeps its line
+// endings.
+fn update(path: &Path, task: &str, change: impl FnOnce(&str) -> String) -> io::Result<bool> {
+    match update_contents(&fs::read_to_string(path)?, task, change) {
+        Some(contents) => {
+            data::write_atomically(path, contents.as_bytes())?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+// The contents with the first unfinished line working on `task` changed, or `None` without one.
+// Every other byte, line endings included, stays as it was.
+fn update_contents(
+    contents: &str,
+    task: &str,
+    change: impl FnOnce(&str) -> String,
+) -> Option<String> {
+    let mut offset = 0;
+    for line in contents.split_inclusive('\n') {
+        let body = line.trim_end_matches(['\r', '\n']);
+        if Item::parse(body).is_some_and(|item| item.task == task) {
+            let end = offset + body.len();
+            return Some(format!(
+                "{}{}{}",
+                &contents[..offset],
+                change(body),
+                &contents[end..]
+            ));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+// Counts a completed pomodoro on the item working on `task` in its pomo:N tag, adding the tag if
+// it isn't there yet.
+pub fn count_pomodoro(path: &Path, task: &str) -> io::Result<bool> {
+    update(path, task, count_line)
+}
+
+// Bumps the first pomo:N tag of the line where it is, or adds pomo:1 at the end.
+fn count_line(line: &str) -> String {
+    let mut offset = 0;
+    for word in line.split_whitespace() {
+        let start = offset + line[offset..].find(word).unwrap_or(0);
+        if let Some(n) = pomos(word) {
+            let end = start + word.len();
+            return format!("{}pomo:{}{}", &line[..start], n + 1, &line[end..]);
+        }
+        offset = start + word.len();
+    }
+    let text = line.trim_end();
+    format!("{} pomo:1{}", text, &line[text.len()..])
+}
+
+// Marks the item working on `task` done today, dropping its priority as todo.txt clients do.
+pub fn complete(path: &Path, task: &str) -> io::Result<bool> {
+    update(path, task, |line| {
+        complete_line(line, Local::now().date_naive())
+    })
+}
+
+fn complete_line(line: &str, today: NaiveDate) -> String {
+    let (_, rest) = split_priority(line.trim_start());
+    format!("x {} {}", today.format("%Y-%m-%d"), rest)
+}
+
+// Choosing the next pomodoro's task among the items.
+pub struct Picker {
+    items: Vec<Item>,
+    selected: usize,
+}
+
+impl Picker {
+    // Starts on the item the timer is already working on, if it's there. There's nothing to
+    // pick from without items.
+    pub fn new(items: Vec<Item>, current: Option<&str>) -> Option<Picker> {
+        if items.is_empty() {
+            return None;
+        }
+        let selected = current
+            .and_then(|task| items.iter().position(|item| item.task == task))
+            .unwrap_or(0);
+        Some(Picker { items, selected })
+    }
+
+    pub fn items(&self) -> &[Item] {
+        &self.items
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn item(&self) -> &Item {
+        &self.items[self.selected]
+    }
+
+    pub fn up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        self.selected = (self.selected + 1).min(self.items.len() - 1);
+    }
+
+    // Takes the selected item off the list, e.g. once it's done. Returns whether any are left.
+    pub fn remove(&mut self) -> bool {
+        self.items.remove(self.selected);
+        self.selected = self.selected.min(self.items.len().saturating_sub(1));
+        !self.items.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_items() {
+        let item = Item::parse("(A) 2026-10-01 Review PR +work @desk pomo:2").unwrap();
+        assert_eq!(item.priority, Some('A'));
+        assert_eq!(item.task, "Review PR +work @desk");
+
+        let item = Item::parse("  Call mom  @phone ").unwrap();
+        assert_eq!(item.priority, None);
+        assert_eq!(item.line, "Call mom  @phone");
+        assert_eq!(item.task, "Call mom @phone");
+
+        assert!(Item::parse("x 2026-10-18 Write RFC").is_none());
+        assert!(Item::parse("   ").is_none());
+    }
+
+    #[test]
+    fn splits_the_priority() {
+        assert_eq!(split_priority("(B) Call mom"), (Some('B'), "Call mom"));
+        assert_eq!(split_priority("(b) Call mom"), (None, "(b) Call mom"));
+        assert_eq!(split_priority("(B)Call mom"), (None, "(B)Call mom"));
+        assert_eq!(split_priority("(B)"), (None, "(B)"));
+    }
+
+    #[test]
+    fn counts_pomodoros_in_place() {
+        assert_eq!(
+            count_line("Review PR\tpomo:2 +work"),
+            "Review PR\tpomo:3 +work"
+        );
+        assert_eq!(count_line("Review PR  +work "), "Review PR  +work pomo:1 ");
+        assert_eq!(count_line("pomo:x pomo:1 pomo:5"), "pomo:x pomo:2 pomo:5");
+    }
+
+    #[test]
+    fn completes_items() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        assert_eq!(
+            complete_line("(A) 2026-10-01 Write RFC  +work", today),
+            "x 2026-10-18 2026-10-01 Write RFC  +work"
+        );
+        assert_eq!(complete_line("Call mom", today), "x 2026-10-18 Call mom");
+    }
+
+    #[test]
+    fn keeps_the_rest_of_the_file() {
+        let contents = "x 2026-10-17 Call mom\r\n(A) Call mom\r\nCall mom";
+        assert_eq!(
+            update_contents(contents, "Call mom", count_line).as_deref(),
+            Some("x 2026-10-17 Call mom\r\n(A) Call mom pomo:1\r\nCall mom")
+        );
+        assert_eq!(
+            update_contents("Call mom", "Call mom", count_line).as_deref(),
+            Some("Call mom pomo:1")
+        );
+        assert_eq!(update_contents("Call mom\n", "Write RFC", count_line), None);
+    }
+}
@@ -0,0 +1,79 @@
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+// Kills the process when the test is done with it, even if the test fails.
+struct Running(Child);
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+// Needs dbus-daemon and dbus-monitor: cargo test -- --ignored
+#[test]
+#[ignore]
+fn notifications_reach_the_session_bus() {
+    // a private session bus standing in for the desktop's
+    let mut bus = Running(
+        Command::new("dbus-daemon")
+            .args(["--session", "--nofork", "--nopidfile", "--print-address"])
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("dbus-daemon should start"),
+    );
+    let mut address = String::new();
+    BufReader::new(bus.0.stdout.take().unwrap())
+        .read_line(&mut address)
+        .unwrap();
+    let address = address.trim().to_string();
+
+    let mut monitor = Running(
+        Command::new("dbus-monitor")
+            .args([
+                "--address",
+                &address,
+                "interface='org.freedesktop.Notifications'",
+            ])
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("dbus-monitor should start"),
+    );
+    // give the monitor time to subscribe
+    thread::sleep(Duration::from_millis(500));
+
+    let data = std::env::temp_dir().join(format!("pomodoro-notify-test-{}", std::process::id()));
+    let status = Command::new(env!("CARGO_BIN_EXE_pomodoro"))
+        .args(["--simulate", "--max-pomodoros", "1", "--notify", "--mute"])
+        .env("DBUS_SESSION_BUS_ADDRESS", &address)
+        .env("XDG_DATA_HOME", &data)
+        .env("XDG_CONFIG_HOME", &data)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    thread::sleep(Duration::from_millis(500));
+    let _ = monitor.0.kill();
+    let mut seen = String::new();
+    monitor
+        .0
+        .stdout
+        .take()
+        .unwrap()
+        .read_to_string(&mut seen)
+        .unwrap();
+    let _ = std::fs::remove_dir_all(data);
+
+    assert!(
+        seen.contains("member=Notify"),
+        "no notification in:\n{}",
+        seen
+    );
+    assert!(seen.contains("\"All pomodoros done\""), "{}", seen);
+}